@@ -0,0 +1,53 @@
+use bevy::{
+    ecs::system::SystemState,
+    prelude::*,
+};
+
+use bevy_easy_player_handler::*;
+
+use criterion::{
+    black_box,
+    criterion_group,
+    criterion_main,
+    BenchmarkId,
+    Criterion,
+};
+
+use uuid::Uuid;
+
+const ROSTER_SIZES: [usize; 4] = [8, 64, 512, 4096];
+
+// Spawns a roster of local players and returns the uuid spawned last, the worst case for a linear scan
+fn build_roster(world: &mut World, roster_size: usize) -> Uuid {
+    world.insert_resource(Party::new());
+    world.insert_resource(PlayerIndex::default());
+    let mut last_uuid = Uuid::nil();
+    for n in 0..roster_size {
+        last_uuid = Uuid::now_v7();
+        world.spawn(PlayerBundle::new(None, Some(format!("player_{}", n)), Some(last_uuid), PlayerType::PlayerLocal));
+    }
+    last_uuid
+}
+
+fn bench_player_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("player_lookup");
+    for roster_size in ROSTER_SIZES {
+        let mut world = World::new();
+        let target_uuid = build_roster(&mut world, roster_size);
+        let mut state: SystemState<(Res<Party>, Res<PlayerIndex>, Query<PlayerData>)> = SystemState::new(&mut world);
+
+        group.bench_with_input(BenchmarkId::new("clone_player", roster_size), &target_uuid, |b, target_uuid| {
+            let (party, player_index, player_query) = state.get(&world);
+            b.iter(|| party.clone_player(black_box(target_uuid), &player_index, &player_query).unwrap());
+        });
+
+        group.bench_with_input(BenchmarkId::new("query_scan", roster_size), &target_uuid, |b, target_uuid| {
+            let (_, _, player_query) = state.get(&world);
+            b.iter(|| player_query.iter().find(|player| &player.id.0 == black_box(target_uuid)).map(|player| player.entity).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_player_lookup);
+criterion_main!(benches);
@@ -32,6 +32,7 @@ fn main() {
             .main_player_email(env::var("PLAYER_EMAIL").unwrap().as_str())
             .main_player_username(env::var("PLAYER_USERNAME").unwrap().as_str())
             .party_size(6)
+            .test_ref_player_mode(TestRefPlayerMode::from_env().unwrap())
             .build()
         )
         .insert_resource(EasyVecUiTimer(Timer::from_seconds(0.250, TimerMode::Repeating)))
@@ -113,7 +114,7 @@ pub fn listen_easy_vec_ui_timer(
     dbi: Res<PlayerHandlerInterface>,
     di: Res<DisplayInts>,
     party: Res<Party>,
-    player_query: Query<&PlayerComponent>,
+    player_query: Query<PlayerData>,
     plugin: Res<BevyEasyPlayerHandlerPlugin>,
 ) {
     if ui_timer.0.finished() {
@@ -137,7 +138,7 @@ pub fn easy_vec_ui(
     dbi: Res<PlayerHandlerInterface>,
     di: Res<DisplayInts>,
     party: Res<Party>,
-    player_query: Query<&PlayerComponent>,
+    player_query: Query<PlayerData>,
     plugin: Res<BevyEasyPlayerHandlerPlugin>,
 ) -> Result<(), ErrorTypePlayerHandler> {
 
@@ -190,12 +191,11 @@ pub fn easy_vec_ui(
 
     // --- Party information --- //
     for player in player_query.iter() {
-        let player_data = player.player.lock().unwrap();
-        let player_uuid = player_data.get_player_id()?;
-        let player_username = player_data.get_player_username()?;
-        let player_type = player_data.get_player_type()?;
+        let player_uuid = player.id.0;
+        let player_username = player.name.0.as_deref().unwrap_or_default();
+        let player_type = &player.kind.0;
         right_data_vec.push(format!(
-            "Querried '&PlayerComponent': [ {} ]\n
+            "Querried 'PlayerData': [ {} ]\n
             Name: [ {} ], Type: [ {:?} ]{}", 
             player_uuid,
             player_username,
@@ -268,15 +268,16 @@ pub fn easy_vec_ui(
 pub fn temp_interface(
     mut commands: Commands,
     keys: Res<ButtonInput<KeyCode>>,
-    db: Res<DatabaseConnection>,
+    storage: Res<PlayerStorage>,
     dbi: Res<PlayerHandlerInterface>,
     mut di: ResMut<DisplayInts>,
-    player_query: Query<&PlayerComponent>,
-    entity_player_query: Query<(Entity, &PlayerComponent)>,
+    player_index: Res<PlayerIndex>,
+    player_query: Query<PlayerData>,
+    entity_player_query: Query<(Entity, &PlayerId)>,
     mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
     mut party: ResMut<Party>,
 ) { 
-    let db_player_vec = match dbi.query_db_existing_players(&db){
+    let db_player_vec = match storage.list_players() {
         Ok(vec) => vec,
         Err(e) => {
             warn!("Error: easy_vec_ui -> db_pipeline_action_query_existing_players: [{:?}]", e);
@@ -332,7 +333,7 @@ pub fn temp_interface(
                 Ok(()) => {},
                 Err(e) => warn!("Error: temp_interface -> di.set_db_target_int(0) Error:[{:?}]", e),
             };
-            match dbi.pipeline_db_and_party_remove_all_build_test_ref_and_init_new_main_player(&db, &mut commands, &entity_player_query, &mut party, &mut plugin) {
+            match dbi.pipeline_db_and_party_remove_all_build_test_ref_and_init_new_main_player(&storage, &mut commands, &entity_player_query, &mut party, &mut plugin) {
                 Ok(_) => {},
                 Err(e) => warn!("Error: temp_interface -> dbi.pipeline_db_and_party_reset_all_and_init_test_ref_and_main_player Error:[{:?}]", e),
             };
@@ -404,7 +405,7 @@ pub fn temp_interface(
                     },
                 };
             }
-            match dbi.pipeline_db_and_party_action_remove_player(&mut commands, &db, &entity_player_query, &mut party, &player_query, stored_id, &mut plugin) {
+            match dbi.pipeline_db_and_party_action_remove_player(&mut commands, &storage, &mut party, &player_index, stored_id, &mut plugin) {
                 // match dbi.pipeline_db_and_party_action_remove_player(&db, &mut party, &mut plugin, stored_id) {
                 Ok(_) => {},
                 Err(e) => warn!("Error: temp_interface -> {} -> pipeline_db_and_party_action_remove_player [{:?}]", &db_target_uuid, e),
@@ -419,7 +420,7 @@ pub fn temp_interface(
             let db_target = &db_player_vec[db_target_idx as usize];
             let db_target_uuid_str = db_target.get_uuid_string().as_str();
             let db_target_uuid = Uuid::try_parse(db_target_uuid_str).unwrap();
-            match dbi.pipeline_db_and_party_add_player_from_db_to_party(&mut commands, &db, &db_target_uuid, &mut party, &player_query, &mut plugin) {
+            match dbi.pipeline_db_and_party_add_player_from_db_to_party(&mut commands, &storage, &db_target_uuid, &mut party, &player_query, &mut plugin) {
                 Ok(_) => {},
                 Err(e) => warn!("Error: temp_interface -> pipeline_db_and_party_add_player_from_db_to_party [{:?}]", e),
             };
@@ -430,24 +431,15 @@ pub fn temp_interface(
     if keys.just_released(KeyCode::Numpad3) {
         info!("just_released: Numpad3");  
         {
-            let stored_uuid = match party.clone_active_player_uuid(&player_query) {
+            let stored_uuid = match party.clone_active_player_uuid(&player_index, &player_query) {
                 Ok(id) => id,
                 Err(e) => {
                     warn!("Error: temp_interface -> party.clone_active_player_uuid() Error:[{:?}]", e);
                     return ()
                 },
             };
-            for player in entity_player_query.iter() {
-                let player_mutex = match player.1.player.lock() {
-                    Ok(player_mutex) => player_mutex,
-                    Err(e) => {
-                        panic!("Error: temp_interface -> match player.1.player.lock() Error:[{:?}]", e);
-                    },
-                };
-                let player_uuid = player_mutex.get_player_id().expect("Error: temp_interface -> player_mutex.get_player_id failed...");
-                if player_uuid == &stored_uuid {
-                    commands.entity(player.0).despawn_recursive();
-                }
+            if let Some(entity) = player_index.get_entity(&stored_uuid) {
+                commands.entity(entity).despawn_recursive();
             }
         }
     };
@@ -478,7 +470,7 @@ pub fn temp_interface(
         info!("just_released: Numpad1"); 
         {
             if party_count > 1 {
-                let stored_uuid = match party.clone_active_player_uuid(&player_query) {
+                let stored_uuid = match party.clone_active_player_uuid(&player_index, &player_query) {
                     Ok(id) => id,
                     Err(e) => {
                         warn!("Error: temp_interface -> party.clone_active_player_uuid() Error:[{:?}]", e);
@@ -490,7 +482,7 @@ pub fn temp_interface(
                     Err(e) => warn!("Error: temp_interface -> party.set_active_player_index() Error:[{:?}]", e),
                 };
                 println!("active_player_get_player_id -> stored_uuid: [{}]", stored_uuid);
-                match party.remove_player(&mut commands, &entity_player_query, &mut plugin, &stored_uuid) {
+                match party.remove_player(&mut commands, &player_index, &mut plugin, &stored_uuid) {
                     Ok(()) => (),
                     Err(e) => warn!("Error: temp_interface -> party.players_remove_player Error:[{:?}]", e),
                 };
@@ -9,8 +9,11 @@ use rusqlite::Result;
 use uuid::Uuid;
 
 use crate::{
+    handlers::session::unix_timestamp_now,
+    PlayerDbTx,
     PlayerHandlerInterface, 
     PlayerType,
+    ProfileValue,
 };
 
 impl PlayerHandlerInterface {
@@ -18,55 +21,16 @@ impl PlayerHandlerInterface {
         &self,
         db: &Res<DatabaseConnection>,
     ) -> Result<i32, ErrorTypePlayerHandler> {
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
-        
-        // Execute the SQL statement to verify if the player table exists
-        let party_player_count_query: i32 = conn.query_row(
-            "SELECT COUNT(*) AS PartyPlayerCount FROM player_table;",
-            (),
-            |row| row.get(0),
-        )
-        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("Player Count failed, Error: [{}]", e)))?;
-
-        Ok(party_player_count_query)
+        self.with_db_tx(db, |tx| tx.action_count_players_in_db())
     }
 
     pub fn action_table_player_init(
         &self,
         db: &Res<DatabaseConnection>,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
-        
-        // Execute the SQL statement to create the player table
-        conn.execute(
-            "CREATE TABLE player_table (
-                uuid TEXT PRIMARY KEY,
-                email BLOB,
-                username BLOB
-            )",
-            (),
-        )
-        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("Player Table Creation Failed [{}]", e)))?; // Map any error to ErrorTypePlayerHandler and propagate it
-
-        Ok(()) // Return success if the table is created without errors
+        // The migrations own the player_table schema, running them from an empty database builds the latest table
+        self.action_run_migrations(db)?;
+        Ok(())
     }
 
     pub fn action_insert_player_record(
@@ -77,53 +41,334 @@ impl PlayerHandlerInterface {
         main_player_username: Option<&String>,
         player_type: PlayerType,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                println!("Error 1 [ action_insert_player_record ]");
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
-        let insert_target = String::from(*main_player_uuid);
-        conn.execute(
-            "INSERT INTO player_table (uuid, email, username) VALUES (?1, ?2, ?3)",
-            (insert_target, main_player_email, main_player_username),
-        )
-            .map_err(|e| match player_type {
-                PlayerType::PlayerAiLocal => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Ai into 'player_table' failed Error: [{}]", e)),
-                PlayerType::PlayerAiRemote => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Remote Player Ai into 'player_table' failed Error: [{}]", e)),
-                PlayerType::PlayerLocal => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Local into 'player_table' failed Error: [{}]", e)),
-                PlayerType::PlayerMain => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Main into 'player_table' failed Error: [{}]", e)),
-                PlayerType::PlayerRemote => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Remote Local into 'player_table' failed Error: [{}]", e)),
-                PlayerType::PlayerTestRef => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Test Reference Local into 'player_table' failed Error: [{}]", e)),
-            })?;
-        Ok(())
+        self.with_db_tx(db, |tx| tx.action_insert_player_record(main_player_uuid, main_player_email, main_player_username, player_type))
+    }
+
+    pub fn action_set_main_player_record(
+        &self,
+        db: &Res<DatabaseConnection>,
+        main_player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_set_main_player_record(main_player_uuid))
     }
 
     pub fn action_remove_all_player_records(
         &self,
         db: &Res<DatabaseConnection>,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
-
-        conn.execute(
-            "DELETE FROM player_table",
-            (),
+        self.with_db_tx(db, |tx| tx.action_remove_all_player_records())
+    }
+
+    pub fn action_set_player_profile_value(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        key: &str,
+        value: impl Into<ProfileValue>,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_set_player_profile_value(player_uuid, key, value))
+    }
+
+    pub fn action_remove_player_profile_value(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_remove_player_profile_value(player_uuid, key))
+    }
+
+    pub fn action_set_player_stat(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        stat: &str,
+        value: i64,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_set_player_stat(player_uuid, stat, value))
+    }
+
+    pub fn action_increment_player_stat(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        stat: &str,
+        by: i64,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_increment_player_stat(player_uuid, stat, by))
+    }
+
+    pub fn action_write_player_stats(
+        &self,
+        db: &Res<DatabaseConnection>,
+        batch: &[(Uuid, String, i64)],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_write_player_stats(batch))
+    }
+
+    pub fn action_begin_session(
+        &self,
+        db: &Res<DatabaseConnection>,
+        players: &[(usize, Uuid)],
+    ) -> Result<i64, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_begin_session(players))
+    }
+
+    pub fn action_add_session_player(
+        &self,
+        db: &Res<DatabaseConnection>,
+        session_id: i64,
+        seat: usize,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_add_session_player(session_id, seat, player_uuid))
+    }
+
+    pub fn action_end_session(
+        &self,
+        db: &Res<DatabaseConnection>,
+        session_id: i64,
+        players: &[Uuid],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_end_session(session_id, players))
+    }
+
+    pub fn action_save_party_preset(
+        &self,
+        db: &Res<DatabaseConnection>,
+        name: &str,
+        members: &[(usize, Uuid)],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_save_party_preset(name, members))
+    }
+
+    pub fn action_delete_party_preset(
+        &self,
+        db: &Res<DatabaseConnection>,
+        name: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.action_delete_party_preset(name))
+    }
+}
+
+// Profile, stats, session and preset writes, so they can share a transaction with the player_table steps
+impl<'conn> PlayerDbTx<'conn> {
+    pub fn action_set_player_profile_value(
+        &self,
+        player_uuid: &Uuid,
+        key: &str,
+        value: impl Into<ProfileValue>,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        // Only players with a record can hold a profile, the select writes nothing otherwise
+        let value: ProfileValue = value.into();
+        let written = self.tx.execute(
+            "INSERT INTO player_profile (uuid, key, value_type, value)
+                SELECT ?1, ?2, ?3, ?4 WHERE EXISTS (SELECT 1 FROM player_table WHERE uuid = ?1)
+                ON CONFLICT (uuid, key) DO UPDATE SET value_type = excluded.value_type, value = excluded.value",
+            (String::from(*player_uuid), key, value.to_db_code(), &value),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_set_player_profile_value [{}] failed Error: [{}]", key, e)))?;
+        if written == 0 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_set_player_profile_value failed: no player record matches [{}]", player_uuid)))
+        }
+
+        Ok(())
+    }
+
+    pub fn action_remove_player_profile_value(
+        &self,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "DELETE FROM player_profile WHERE uuid = ?1 AND key = ?2",
+            (String::from(*player_uuid), key),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_player_profile_value [{}] failed Error: [{}]", key, e)))?;
+
+        Ok(())
+    }
+
+    pub fn action_set_player_stat(
+        &self,
+        player_uuid: &Uuid,
+        stat: &str,
+        value: i64,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let written = self.tx.execute(
+            "INSERT INTO player_stats (uuid, stat, value)
+                SELECT ?1, ?2, ?3 WHERE EXISTS (SELECT 1 FROM player_table WHERE uuid = ?1)
+                ON CONFLICT (uuid, stat) DO UPDATE SET value = excluded.value",
+            (String::from(*player_uuid), stat, value),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_set_player_stat [{}] failed Error: [{}]", stat, e)))?;
+        if written == 0 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_set_player_stat failed: no player record matches [{}]", player_uuid)))
+        }
+
+        Ok(())
+    }
+
+    // Adds to the stored value in place, for players whose PlayerStats are not in the world
+    pub fn action_increment_player_stat(
+        &self,
+        player_uuid: &Uuid,
+        stat: &str,
+        by: i64,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let written = self.tx.execute(
+            "INSERT INTO player_stats (uuid, stat, value)
+                SELECT ?1, ?2, ?3 WHERE EXISTS (SELECT 1 FROM player_table WHERE uuid = ?1)
+                ON CONFLICT (uuid, stat) DO UPDATE SET value = value + excluded.value",
+            (String::from(*player_uuid), stat, by),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_increment_player_stat [{}] failed Error: [{}]", stat, e)))?;
+        if written == 0 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_increment_player_stat failed: no player record matches [{}]", player_uuid)))
+        }
+
+        Ok(())
+    }
+
+    // Batched write used by flush_player_stats, rows for players without a record are skipped
+    pub fn action_write_player_stats(
+        &self,
+        batch: &[(Uuid, String, i64)],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare(
+                "INSERT INTO player_stats (uuid, stat, value)
+                SELECT ?1, ?2, ?3 WHERE EXISTS (SELECT 1 FROM player_table WHERE uuid = ?1)
+                ON CONFLICT (uuid, stat) DO UPDATE SET value = excluded.value",
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_write_player_stats: prepare failed Error: [{}]", e)))?;
+        for (player_uuid, stat, value) in batch.iter() {
+            stmt.execute((String::from(*player_uuid), stat, value))
+                .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_write_player_stats [{}] [{}] failed Error: [{}]", player_uuid, stat, e)))?;
+        }
+
+        Ok(())
+    }
+
+    // Opens a session_table row with the current party and marks them as seen, returns the new session id
+    pub fn action_begin_session(
+        &self,
+        players: &[(usize, Uuid)],
+    ) -> Result<i64, ErrorTypePlayerHandler> {
+        let now = unix_timestamp_now();
+        self.tx.execute(
+            "INSERT INTO session_table (started_at) VALUES (?1)",
+            [now],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_begin_session failed Error: [{}]", e)))?;
+        let session_id = self.tx.last_insert_rowid();
+        for (seat, player_uuid) in players.iter() {
+            self.tx.execute(
+                "INSERT OR IGNORE INTO session_players (session_id, uuid, seat) VALUES (?1, ?2, ?3)",
+                (session_id, String::from(*player_uuid), *seat as i64),
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_begin_session: adding [{}] failed Error: [{}]", player_uuid, e)))?;
+            self.tx.execute(
+                "UPDATE player_table SET last_seen_at = ?2 WHERE uuid = ?1",
+                (String::from(*player_uuid), now),
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_begin_session: marking [{}] seen failed Error: [{}]", player_uuid, e)))?;
+        }
+
+        Ok(session_id)
+    }
+
+    pub fn action_add_session_player(
+        &self,
+        session_id: i64,
+        seat: usize,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "INSERT OR IGNORE INTO session_players (session_id, uuid, seat) VALUES (?1, ?2, ?3)",
+            (session_id, String::from(*player_uuid), seat as i64),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_add_session_player [{}] failed Error: [{}]", player_uuid, e)))?;
+        self.tx.execute(
+            "UPDATE player_table SET last_seen_at = ?2 WHERE uuid = ?1",
+            (String::from(*player_uuid), unix_timestamp_now()),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_add_session_player: marking [{}] seen failed Error: [{}]", player_uuid, e)))?;
+
+        Ok(())
+    }
+
+    // Closes the session and marks the players still in the party as seen
+    pub fn action_end_session(
+        &self,
+        session_id: i64,
+        players: &[Uuid],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let now = unix_timestamp_now();
+        let ended = self.tx.execute(
+            "UPDATE session_table SET ended_at = ?2 WHERE session_id = ?1",
+            (session_id, now),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_end_session failed Error: [{}]", e)))?;
+        if ended == 0 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_end_session failed: no session matches [{}]", session_id)))
+        }
+        for player_uuid in players.iter() {
+            self.tx.execute(
+                "UPDATE player_table SET last_seen_at = ?2 WHERE uuid = ?1",
+                (String::from(*player_uuid), now),
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_end_session: marking [{}] seen failed Error: [{}]", player_uuid, e)))?;
+        }
+
+        Ok(())
+    }
+
+    // Saving under a name that is already taken replaces that preset
+    pub fn action_save_party_preset(
+        &self,
+        name: &str,
+        members: &[(usize, Uuid)],
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "DELETE FROM party_preset_member WHERE name = ?1",
+            [name],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_save_party_preset: clearing [{}] failed Error: [{}]", name, e)))?;
+        self.tx.execute(
+            "INSERT INTO party_preset (name, created_at) VALUES (?1, ?2)
+                ON CONFLICT (name) DO UPDATE SET created_at = excluded.created_at",
+            (name, unix_timestamp_now()),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_save_party_preset [{}] failed Error: [{}]", name, e)))?;
+        for (seat, player_uuid) in members.iter() {
+            self.tx.execute(
+                "INSERT INTO party_preset_member (name, seat, uuid) VALUES (?1, ?2, ?3)",
+                (name, *seat as i64, String::from(*player_uuid)),
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_save_party_preset: adding [{}] failed Error: [{}]", player_uuid, e)))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn action_delete_party_preset(
+        &self,
+        name: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "DELETE FROM party_preset_member WHERE name = ?1",
+            [name],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_delete_party_preset: clearing [{}] failed Error: [{}]", name, e)))?;
+        let deleted = self.tx.execute(
+            "DELETE FROM party_preset WHERE name = ?1",
+            [name],
         )
-        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_all_player_records failed Error: [{}]", e)))?;
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_delete_party_preset [{}] failed Error: [{}]", name, e)))?;
+        if deleted == 0 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_delete_party_preset failed: no preset named [{}]", name)))
+        }
 
         Ok(())
     }
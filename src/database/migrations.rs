@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::{
+    DatabaseConnection,
+    ErrorTypePlayerHandler,
+};
+
+use rusqlite::{
+    Connection,
+    Result,
+};
+
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    PlayerHandlerError,
+    PlayerHandlerInterface,
+    PlayerType,
+};
+
+// The schema version is tracked through PRAGMA user_version, a fresh database reports 0.
+// Bump this alongside every new entry pushed onto PLAYER_TABLE_MIGRATIONS.
+pub const PLAYER_TABLE_SCHEMA_VERSION: i32 = 7;
+
+pub struct PlayerTableMigration {
+    pub version: i32,
+    pub description: &'static str,
+    pub apply: fn(&Connection, &PlayerTableMigrationContext) -> Result<(), rusqlite::Error>,
+}
+
+// What the host app has configured that a backfill can rely on, SqlitePlayerStore fills it in from the plugin
+#[derive(Clone, Debug, Default)]
+pub struct PlayerTableMigrationContext {
+    pub test_ref_uuid: Option<Uuid>,
+}
+
+// Ordered oldest to newest, each migration has to be safe to re-run against a database that already has its changes.
+pub const PLAYER_TABLE_MIGRATIONS: &[PlayerTableMigration] = &[
+    PlayerTableMigration {
+        version: 1,
+        description: "create player_table",
+        apply: migration_001_create_player_table,
+    },
+    PlayerTableMigration {
+        version: 2,
+        description: "add player_table.player_type",
+        apply: migration_002_add_player_type,
+    },
+    PlayerTableMigration {
+        version: 3,
+        description: "add player_table.is_main",
+        apply: migration_003_add_is_main,
+    },
+    PlayerTableMigration {
+        version: 4,
+        description: "create player_profile",
+        apply: migration_004_create_player_profile,
+    },
+    PlayerTableMigration {
+        version: 5,
+        description: "create player_stats",
+        apply: migration_005_create_player_stats,
+    },
+    PlayerTableMigration {
+        version: 6,
+        description: "add player_table timestamps and create session tables",
+        apply: migration_006_add_timestamps_and_sessions,
+    },
+    PlayerTableMigration {
+        version: 7,
+        description: "create party_preset and party_preset_member",
+        apply: migration_007_create_party_presets,
+    },
+];
+
+fn migration_001_create_player_table(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS player_table (
+            uuid TEXT PRIMARY KEY,
+            email BLOB,
+            username BLOB
+        )",
+        (),
+    )?;
+    Ok(())
+}
+
+fn migration_002_add_player_type(conn: &Connection, context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    if table_has_column(conn, "player_table", "player_type")? {
+        return Ok(());
+    }
+    conn.execute(
+        &format!(
+            "ALTER TABLE player_table ADD COLUMN player_type INTEGER NOT NULL DEFAULT {}",
+            PlayerType::PlayerLocal.to_db_code(),
+        ),
+        (),
+    )?;
+
+    // Legacy rows are only retyped when they can be identified: the AI username the old matching used,
+    // and the configured test reference. Everything else stays PlayerLocal.
+    conn.execute(
+        "UPDATE player_table SET player_type = ?1 WHERE username = 'PlayerAiLocal'",
+        [PlayerType::PlayerAiLocal.to_db_code()],
+    )?;
+    if let Some(test_ref_uuid) = context.test_ref_uuid {
+        conn.execute(
+            "UPDATE player_table SET player_type = ?1 WHERE uuid = ?2",
+            (PlayerType::PlayerTestRef.to_db_code(), test_ref_uuid.to_string()),
+        )?;
+    }
+    Ok(())
+}
+
+fn migration_003_add_is_main(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    if !table_has_column(conn, "player_table", "is_main")? {
+        conn.execute(
+            "ALTER TABLE player_table ADD COLUMN is_main INTEGER NOT NULL DEFAULT 0",
+            (),
+        )?;
+    }
+    // Only one record can ever carry the main player marker
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS player_table_single_main ON player_table (is_main) WHERE is_main = 1",
+        (),
+    )?;
+
+    // Mark the oldest stored main player when nothing is marked yet
+    conn.execute(
+        "UPDATE player_table SET is_main = 1
+            WHERE NOT EXISTS (SELECT 1 FROM player_table WHERE is_main = 1)
+            AND rowid = (SELECT rowid FROM player_table WHERE player_type = ?1 ORDER BY rowid LIMIT 1)",
+        [PlayerType::PlayerMain.to_db_code()],
+    )?;
+    Ok(())
+}
+
+// One row per player and key. value has no declared type so SQLite keeps whatever was written, value_type tells a stored 1 apart as a bool or an integer
+fn migration_004_create_player_profile(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS player_profile (
+            uuid TEXT NOT NULL REFERENCES player_table (uuid),
+            key TEXT NOT NULL,
+            value_type INTEGER NOT NULL,
+            value,
+            PRIMARY KEY (uuid, key)
+        )",
+        (),
+    )?;
+    Ok(())
+}
+
+fn migration_005_create_player_stats(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS player_stats (
+            uuid TEXT NOT NULL REFERENCES player_table (uuid),
+            stat TEXT NOT NULL,
+            value INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (uuid, stat)
+        )",
+        (),
+    )?;
+    // Leaderboards read a single stat across every player
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS player_stats_by_stat ON player_stats (stat, value)",
+        (),
+    )?;
+    Ok(())
+}
+
+// Timestamps are unix seconds. Existing records count as created now, when they were last seen is unknown
+fn migration_006_add_timestamps_and_sessions(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    if !table_has_column(conn, "player_table", "created_at")? {
+        conn.execute(
+            "ALTER TABLE player_table ADD COLUMN created_at INTEGER",
+            (),
+        )?;
+    }
+    if !table_has_column(conn, "player_table", "last_seen_at")? {
+        conn.execute(
+            "ALTER TABLE player_table ADD COLUMN last_seen_at INTEGER",
+            (),
+        )?;
+    }
+    conn.execute(
+        "UPDATE player_table SET created_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE created_at IS NULL",
+        (),
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_table (
+            session_id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER
+        )",
+        (),
+    )?;
+    // Everyone who was in the party at some point of the session, with the seat they joined in
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS session_players (
+            session_id INTEGER NOT NULL REFERENCES session_table (session_id),
+            uuid TEXT NOT NULL,
+            seat INTEGER NOT NULL,
+            PRIMARY KEY (session_id, uuid)
+        )",
+        (),
+    )?;
+    Ok(())
+}
+
+fn migration_007_create_party_presets(conn: &Connection, _context: &PlayerTableMigrationContext) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS party_preset (
+            name TEXT PRIMARY KEY,
+            created_at INTEGER NOT NULL
+        )",
+        (),
+    )?;
+    // Members are kept after their player record is deleted, loading the preset reports them as missing
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS party_preset_member (
+            name TEXT NOT NULL REFERENCES party_preset (name),
+            seat INTEGER NOT NULL,
+            uuid TEXT NOT NULL,
+            PRIMARY KEY (name, seat)
+        )",
+        (),
+    )?;
+    Ok(())
+}
+
+fn table_has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(1)?;
+        if name == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+impl PlayerHandlerInterface {
+    pub fn query_db_schema_version(
+        &self,
+        db: &Res<DatabaseConnection>,
+    ) -> Result<i32, ErrorTypePlayerHandler> {
+        // Get and Lock the mutex to access the database connection
+        let conn = db.get_connection();
+        let conn = conn.lock();
+        let conn = match conn {
+            Ok(conn) => conn,
+            Err(_) => {
+                error!("Database connection lock poisoned.");
+                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
+            }
+        };
+
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_schema_version failed Error: [{}]", e)))?;
+
+        Ok(version)
+    }
+
+    // Without a test reference uuid, legacy rows only get the username backfill
+    pub fn action_run_migrations(
+        &self,
+        db: &Res<DatabaseConnection>,
+    ) -> Result<i32, PlayerHandlerError> {
+        self.action_run_migrations_with_context(db, &PlayerTableMigrationContext::default())
+    }
+
+    pub fn action_run_migrations_with_context(
+        &self,
+        db: &Res<DatabaseConnection>,
+        context: &PlayerTableMigrationContext,
+    ) -> Result<i32, PlayerHandlerError> {
+        run_player_table_migrations(&db.get_connection(), context)
+    }
+}
+
+// Shared by action_run_migrations and SqlitePlayerStore::load, returns the schema version the database ends up on
+pub(crate) fn run_player_table_migrations(
+    conn: &Arc<Mutex<Connection>>,
+    context: &PlayerTableMigrationContext,
+) -> Result<i32, PlayerHandlerError> {
+    // Get and Lock the mutex to access the database connection
+    let conn = conn.lock();
+    let mut conn = match conn {
+        Ok(conn) => conn,
+        Err(_) => {
+            error!("Database connection lock poisoned.");
+            return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned.into());
+        }
+    };
+
+    let mut version: i32 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("action_run_migrations: reading user_version failed Error: [{}]", e)))?;
+
+    if version > PLAYER_TABLE_SCHEMA_VERSION {
+        return Err(PlayerHandlerError::SchemaTooNew {
+            found: version,
+            supported: PLAYER_TABLE_SCHEMA_VERSION,
+        });
+    }
+
+    for migration in PLAYER_TABLE_MIGRATIONS.iter() {
+        if migration.version <= version {
+            continue;
+        }
+        info!("action_run_migrations: applying [{}] {}", migration.version, migration.description);
+        let tx = conn
+            .transaction()
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_run_migrations: begin [{}] failed Error: [{}]", migration.version, e)))?;
+        (migration.apply)(&tx, context)
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_run_migrations: [{}] {} failed Error: [{}]", migration.version, migration.description, e)))?;
+        tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_run_migrations: recording [{}] failed Error: [{}]", migration.version, e)))?;
+        tx.commit()
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_run_migrations: commit [{}] failed Error: [{}]", migration.version, e)))?;
+        version = migration.version;
+    }
+
+    Ok(version)
+}
@@ -1,3 +1,5 @@
 pub mod actions;
+pub mod migrations;
 pub mod queries;
-pub mod pipeline_db_and_party;
\ No newline at end of file
+pub mod pipeline_db_and_party;
+pub mod transaction;
\ No newline at end of file
@@ -1,34 +1,31 @@
 use bevy::prelude::*;
 
-use bevy_easy_shared_definitions::{
-    DatabaseConnection, 
-    ErrorTypePlayerHandler,
-};
-
-use std::{
-    env,
-    env::VarError,
-    sync::{
-        Arc,
-        Mutex,
-    },
-};
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
 
-use dotenv::dotenv;
 use rusqlite::Result;
 use uuid::Uuid;
 
 use crate::{
+    handlers::{
+        stats::discard_unsaved_player_stats,
+        store::insert_test_ref_record,
+    },
     BevyEasyPlayerHandlerPlugin, 
+    DBPlayer,
     Party, 
+    PartyEvent,
     Player, 
-    PlayerAiLocal,
-    PlayerComponent, 
+    PlayerBundle,
+    PlayerData,
+    PlayerHandlerError,
     PlayerHandlerInterface, 
-    PlayerLocal, 
-    PlayerMain,
+    PlayerId,
+    PlayerIndex,
+    PlayerRecordDeleted,
+    PlayerRecordInserted,
+    PlayerStorage,
     PlayerType,
-    PlayerRemote,
+    TestRefPlayerMode,
 };
 
 impl PlayerHandlerInterface {
@@ -36,7 +33,7 @@ impl PlayerHandlerInterface {
         &self,
         commands: &mut Commands,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_query: &Query<PlayerData>,
         plugin: &ResMut<BevyEasyPlayerHandlerPlugin>,
         username: &str,
     ) -> Result<(), ErrorTypePlayerHandler> {
@@ -45,18 +42,15 @@ impl PlayerHandlerInterface {
 
         // Init a new local player and add into the party
         let player_username = String::from(username);
-        let new_player = PlayerAiLocal::new(
+        let new_player = PlayerBundle::new(
             None, 
             Some(player_username.clone()), 
             None,
             PlayerType::PlayerAiLocal,
         );
-        let packaged_player = Arc::new(Mutex::new(new_player));
 
         // party.players_add_player(packaged_player)?;
-        commands.spawn(PlayerComponent{
-            player: packaged_player,
-        });
+        commands.spawn(new_player);
 
         // Get the new party size
         let party_size = party.get_player_count_party(player_query)?;
@@ -70,27 +64,36 @@ impl PlayerHandlerInterface {
         &self,
         commands: &mut Commands,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_query: &Query<PlayerData>,
         plugin: &ResMut<BevyEasyPlayerHandlerPlugin>,
         username: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.pipeline_db_and_party_add_new_synced_player_local_with_uuid(commands, party, player_query, plugin, username, Uuid::now_v7())
+    }
+
+    // Same as pipeline_db_and_party_add_new_synced_player_local, for callers that need the new player's uuid up front
+    pub fn pipeline_db_and_party_add_new_synced_player_local_with_uuid(
+        &self,
+        commands: &mut Commands,
+        party: &mut ResMut<Party>,
+        player_query: &Query<PlayerData>,
+        plugin: &ResMut<BevyEasyPlayerHandlerPlugin>,
+        username: &str,
+        synced_uuid: Uuid,
     ) -> Result<(), ErrorTypePlayerHandler> {
         // Party Size Management Checks
         self.verify_if_party_size_exceeds_limit(plugin, party, player_query)?;
 
         // Init a new local player and add into the party
         let player_username = String::from(username);
-        let synced_uuid = Uuid::now_v7();
-        let new_player = PlayerLocal::new(
+        let new_player = PlayerBundle::new(
             None, 
             Some(player_username.clone()), 
             Some(synced_uuid),
             PlayerType::PlayerLocal,
         );
-        let packaged_player: Arc<Mutex<PlayerLocal>> = Arc::new(Mutex::new(new_player));
         
-        commands.spawn(PlayerComponent{
-            player: packaged_player,
-        });
+        commands.spawn(new_player);
 
         // Get the new party size
         let party_size = party.get_player_count_party(player_query)?;
@@ -101,57 +104,67 @@ impl PlayerHandlerInterface {
     pub fn pipeline_db_and_party_action_remove_player(
         &self,
         commands: &mut Commands,
-        db: &Res<DatabaseConnection>,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>,
+        storage: &PlayerStorage,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
         player_uuid: &Uuid,
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
-    ) -> Result<(), ErrorTypePlayerHandler> {
+    ) -> Result<(), PlayerHandlerError> {
         // grab the test ref player ref uuid 
-        let test_ref: (Uuid, String, String) = self.test_ref_info()?;
+        let test_ref = self.test_ref_info(plugin)?;
 
-        if test_ref.0 == *player_uuid {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("[ pipeline_db_and_party_action_remove_player: {} ] Failed: target is the test reference player, and can be not removed", &player_uuid)))
+        if test_ref.map(|test_ref| test_ref.0) == Some(*player_uuid) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("[ pipeline_db_and_party_action_remove_player: {} ] Failed: target is the test reference player, and can be not removed", &player_uuid)).into())
         }  
         // grab the main player ref uuid 
-        let player_uuids = party.get_all_players_ids(player_query)?;
+        let main_player_uuid = plugin.get_main_player_uuid()?.copied();
 
-        if player_uuids[0] == *player_uuid {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("[ pipeline_db_and_party_action_remove_player: {} ] Failed: target is the main player, and can be not removed", &player_uuid)))
+        if main_player_uuid == Some(*player_uuid) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("[ pipeline_db_and_party_action_remove_player: {} ] Failed: target is the main player, and can be not removed", &player_uuid)).into())
         }      
         
-        let mut remove_player = false;
-        for uuid in player_uuids.iter() {
-            if player_uuid == uuid {
-                remove_player = true;
+        // Locate the party entity up front, the despawn is only queued once every fallible step has succeeded
+        let target_entity = player_index.get_entity(player_uuid);
+        
+        // Snapshot the party so a failed removal leaves it as it was
+        let player_map_snapshot = party.player_map.clone();
+        let active_player_snapshot = party.get_active_player_index()?;
+        let pending_events_snapshot = party.pending_events.len();
+        let revision_snapshot = party.revision;
+        let delta_log_snapshot = party.delta_log.len();
+
+        // The record and the party seat go together, an error from either rolls the transaction back
+        let removed = storage.with_transaction(|records| {
+            if !records.player_exists(player_uuid)? {
+                return Ok(false);
+            }
+            records.remove_player(player_uuid)?;
+            if party.verify_player_exists_player_map_uuid(player_uuid)? {
+                party.player_map_remove_player(plugin, player_uuid)?;
+            }
+            Ok(true)
+        });
+        match removed {
+            Ok(true) => (),
+            Ok(false) => return Err(PlayerHandlerError::PlayerNotFound(*player_uuid)),
+            Err(e) => {
+                party.player_map = player_map_snapshot;
+                party.active_player = active_player_snapshot;
+                party.pending_events.truncate(pending_events_snapshot);
+                party.revision = revision_snapshot;
+                party.delta_log.truncate(delta_log_snapshot);
+                return Err(e.into());
             }
         }
 
-        if remove_player {
-            party.remove_player(commands, entity_player_query, plugin, player_uuid)?;
-        }
-        
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
+        party.queue_event(PartyEvent::PlayerRecordDeleted(PlayerRecordDeleted {
+            player_uuid: *player_uuid,
+        }));
 
-        let player_uuid = player_uuid.to_owned();
-        let player_uuid_string = String::from(player_uuid);
-        let player_uuid_str = player_uuid_string.as_str();
-        // setup and execute deletion of single player record from DB
-        conn.execute(
-            "DELETE FROM player_table WHERE uuid LIKE ?",
-            [format!("%{}%", player_uuid_str)],
-        )
-        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_player_record failed Error: [{}]", e)))?;
+        if let Some(entity) = target_entity {
+            discard_unsaved_player_stats(commands, entity);
+            commands.entity(entity).despawn_recursive();
+        }
 
         Ok(())
     }
@@ -159,32 +172,16 @@ impl PlayerHandlerInterface {
     pub fn pipeline_db_and_party_add_player_from_db_to_party(
         &self,
         commands: &mut Commands,
-        db: &Res<DatabaseConnection>,
+        storage: &PlayerStorage,
         existing_uuid: &Uuid,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_query: &Query<PlayerData>,
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        dotenv().ok();
-        // grab the test ref uuid from .env
-        let test_ref_uuid_string = match env::var("TEST_REF_PLAYER_UUID") {
-            Ok(value) => value,
-            Err(VarError::NotPresent) => {
-                return Err(ErrorTypePlayerHandler::VarErrorNotPresent)
-            },
-            Err(VarError::NotUnicode(err)) => {
-                let err_string = err.into_string().unwrap();
-                return Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err_string))
-            },
-        };
-
-        let test_ref_uuid = match Uuid::try_parse(test_ref_uuid_string.as_str()) {
-            Ok(uuid) => uuid,
-            Err(e) =>{
-                return Err(ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))},
-        };
+        // grab the configured test ref uuid
+        let test_ref = self.test_ref_info(plugin)?;
 
-        if test_ref_uuid == *existing_uuid {
+        if test_ref.map(|test_ref| test_ref.0) == Some(*existing_uuid) {
             return Err(ErrorTypePlayerHandler::AddPlayerFromDbToPartyFailed(format!("Player: [{}] is the test reference, not a valid player", &existing_uuid)))
         }
 
@@ -199,7 +196,7 @@ impl PlayerHandlerInterface {
         self.verify_if_party_size_exceeds_limit(plugin, party, player_query)?;
 
         // query existing players and search for provided uuid
-        let existing_players_vec = self.query_db_existing_players(&db)?;
+        let existing_players_vec = storage.list_players()?;
 
         let mut player_match = false;
         let target_uuid_string_ref = &existing_uuid.to_string();
@@ -208,41 +205,21 @@ impl PlayerHandlerInterface {
             if player_uuid_string_ref == target_uuid_string_ref {
                 // Init a new local player and add into the party
                 let player_username = player.get_username_string();
-                let packaged_player: Arc<Mutex<dyn Player + Send>>  = match player_username.as_str() {
-                    "PlayerAiLocal" => {
-                        let new_player = PlayerAiLocal::new(
-                            None, 
-                            Some(player_username.clone()), 
-                            Some(*existing_uuid),
-                            PlayerType::PlayerAiLocal,
-                        );
-                        let packaged_player: Arc<Mutex<dyn Player + Send>> = Arc::new(Mutex::new(new_player));
-                        packaged_player
-                    },
-                    "PlayerLocal" => {
-                        let new_player = PlayerLocal::new(
-                            None, 
-                            Some(player_username.clone()), 
-                            Some(*existing_uuid),
-                            PlayerType::PlayerLocal,
-                        );
-                        let packaged_player: Arc<Mutex<dyn Player + Send>> = Arc::new(Mutex::new(new_player));
-                        packaged_player
-                    },
-                    &_ => {
-                        let new_player = PlayerRemote::new(
-                            None, 
-                            Some(player_username.clone()), 
-                            Some(*existing_uuid),
-                            PlayerType::PlayerRemote,
-                        );
-                        let packaged_player: Arc<Mutex<dyn Player + Send>> = Arc::new(Mutex::new(new_player));
-                        packaged_player
+                let new_player = match player.get_player_type() {
+                    PlayerType::PlayerAiLocal
+                    | PlayerType::PlayerAiRemote
+                    | PlayerType::PlayerLocal
+                    | PlayerType::PlayerRemote => PlayerBundle::new(
+                        None, 
+                        Some(player_username.clone()), 
+                        Some(*existing_uuid),
+                        player.get_player_type().clone(),
+                    ),
+                    PlayerType::PlayerMain | PlayerType::PlayerTestRef => {
+                        return Err(ErrorTypePlayerHandler::AddPlayerFromDbToPartyFailed(format!("Player: [{}] is stored as [{:?}], which can not be added as a party member", &existing_uuid, player.get_player_type())))
                     },
                 };
-                commands.spawn(PlayerComponent{
-                    player: packaged_player,
-                });
+                commands.spawn(new_player);
                 player_match = true;
             }
         }
@@ -257,31 +234,17 @@ impl PlayerHandlerInterface {
     pub fn pipeline_db_and_party_add_main_player_from_db_to_party(
         &self,
         commands: &mut Commands,
-        db: &Res<DatabaseConnection>,
+        storage: &PlayerStorage,
         existing_uuid: &Uuid,
+        plugin: &BevyEasyPlayerHandlerPlugin,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        dotenv().ok();
-        // grab the test ref uuid from .env
-        let test_ref_uuid_string = match env::var("TEST_REF_PLAYER_UUID") {
-            Ok(value) => value,
-            Err(VarError::NotPresent) => {
-                return Err(ErrorTypePlayerHandler::VarErrorNotPresent)
-            },
-            Err(VarError::NotUnicode(err)) => {
-                let err_string = err.into_string().unwrap();
-                return Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err_string))
-            },
-        };
-        let test_ref_uuid = match Uuid::try_parse(test_ref_uuid_string.as_str()) {
-            Ok(uuid) => uuid,
-            Err(e) =>{
-                return Err(ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))},
-        };
-        if test_ref_uuid == *existing_uuid {
+        // grab the configured test ref uuid
+        let test_ref = self.test_ref_info(plugin)?;
+        if test_ref.map(|test_ref| test_ref.0) == Some(*existing_uuid) {
             return Err(ErrorTypePlayerHandler::AddPlayerFromDbToPartyFailed(format!("Player: [{}] is the test reference, not a valid player", &existing_uuid)))
         }
         // query existing players and search for provided uuid
-        let existing_players_vec = self.query_db_existing_players(&db)?;
+        let existing_players_vec = storage.list_players()?;
         let mut player_match = false;
         let target_uuid_string_ref = &existing_uuid.to_string();
         for player in existing_players_vec {
@@ -289,15 +252,13 @@ impl PlayerHandlerInterface {
             if player_uuid_string_ref == target_uuid_string_ref {
                 // Init a new local player and add into the party
                 let player_username = player.get_username_string();
-                let packaged_player: Arc<Mutex<dyn Player + Send>> = Arc::new(Mutex::new(PlayerMain::new(
+                let new_player = PlayerBundle::new(
                     None, 
                     Some(player_username.clone()), 
                     Some(*existing_uuid),
                     PlayerType::PlayerMain,
-                )));
-                commands.spawn(PlayerComponent{
-                    player: packaged_player,
-                });
+                );
+                commands.spawn(new_player);
                 player_match = true;
             }
         }
@@ -310,77 +271,128 @@ impl PlayerHandlerInterface {
 
     pub fn pipeline_db_and_party_remove_all_build_test_ref_and_init_new_main_player(
         &self,
-        db: &Res<DatabaseConnection>,
+        storage: &PlayerStorage,
         mut commands: &mut Commands,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>, 
+        entity_player_query: &Query<(Entity, &PlayerId)>, 
         party: &mut ResMut<Party>,
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
     ) -> Result<(), ErrorTypePlayerHandler> { 
-        let test_ref_info = self.test_ref_info()?;
-        self.action_remove_all_player_records(&db)?;
-        party.player_map_and_component_remove_all_players(&mut commands, entity_player_query, plugin)?;
-        // Build the test reference player in the DB
-        self.action_insert_player_record(&db, &test_ref_info.0, Some(&test_ref_info.1), Some(&test_ref_info.2), PlayerType::PlayerTestRef)?;    
+        let test_ref_info = self.test_ref_info(plugin)?;
         let main_player_uuid = Uuid::now_v7();
-        // Build the main player
-        plugin.set_main_player_uuid(&main_player_uuid)?;
         let main_player_email = plugin.get_main_player_email()?;
-        let main_player_email = main_player_email.expect("main_player_email unwrap failed ");
+        let main_player_email = main_player_email.expect("main_player_email unwrap failed ").clone();
         let main_player_username = plugin.get_main_player_username()?;
-        let main_player_username = main_player_username.expect("main_player_username unwrap failed ");
-        self.action_insert_player_record(&db, &main_player_uuid, Some(main_player_email), Some(main_player_username), PlayerType::PlayerMain)?;
-        self.pipeline_db_and_party_add_main_player_from_db_to_party(&mut commands, &db, &main_player_uuid)?;
+        let main_player_username = main_player_username.expect("main_player_username unwrap failed ").clone();
+
+        // Clear the table, build the test reference and the new main player as one unit, a failed step leaves the database as it was
+        let (deleted_players, test_ref_inserted) = storage.with_transaction(|records| {
+            let deleted_players = records.list_players()?;
+            records.remove_all_players()?;
+            let test_ref_inserted = insert_test_ref_record(records, test_ref_info.as_ref())?;
+            records.insert_player(&DBPlayer::new(&main_player_uuid, Some(&main_player_email), Some(&main_player_username), PlayerType::PlayerMain))?;
+            records.set_main_player(&main_player_uuid)?;
+            Ok((deleted_players, test_ref_inserted))
+        })?;
+
+        for player in deleted_players.iter() {
+            if let Ok(player_uuid) = Uuid::try_parse(player.get_uuid_string()) {
+                party.queue_event(PartyEvent::PlayerRecordDeleted(PlayerRecordDeleted { player_uuid }));
+            }
+        }
+        for (entity, _) in entity_player_query.iter() {
+            discard_unsaved_player_stats(commands, entity);
+        }
+        party.player_map_and_component_remove_all_players(&mut commands, entity_player_query, plugin)?;
+        if let Some(player_uuid) = test_ref_inserted {
+            party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                player_uuid,
+                player_type: PlayerType::PlayerTestRef,
+            }));
+        }
+        plugin.set_main_player_uuid(&main_player_uuid)?;
+        party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+            player_uuid: main_player_uuid,
+            player_type: PlayerType::PlayerMain,
+        }));
+        self.pipeline_db_and_party_add_main_player_from_db_to_party(commands, storage, &main_player_uuid, plugin)?;
         Ok(())
     }
 
     pub fn pipeline_db_and_party_startup_test_ref_and_init_main_player(
         &self,
-        db: &Res<DatabaseConnection>,
-        mut commands: &mut Commands,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>, 
+        commands: &mut Commands,
+        storage: &PlayerStorage,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        let count = self.query_db_count_existing_players(&db)?;    
-        if count <= 2 { // No non-recoverable/rebuildable records detected
-            let test_ref_info = self.test_ref_info()?;
-            self.action_remove_all_player_records(&db)?;
-            party.player_map_and_component_remove_all_players(&mut commands, entity_player_query, plugin)?;
-            // Build the test reference player in the DB
-            self.action_insert_player_record(&db, &test_ref_info.0, Some(&test_ref_info.1), Some(&test_ref_info.2), PlayerType::PlayerTestRef)?;
-            let main_player_uuid = Uuid::now_v7();
-            let main_player_email = plugin.get_main_player_email()?;
-            let main_player_username = plugin.get_main_player_username()?;
-            // Build the main player
-            self.action_insert_player_record(&db, &main_player_uuid, main_player_email, main_player_username, PlayerType::PlayerMain)?;
-            self.pipeline_db_and_party_add_main_player_from_db_to_party(&mut commands, &db, &main_player_uuid)?;
-        } 
-        else if count > 2 { // If a player already exists in local database, sync the ecs Uuid to match locally stored profile 
-            let players = self.query_db_existing_players(&db)?;
-            for (idx, player) in players.into_iter().enumerate() {
-                if idx == 0 {
-                    let player_id = player.uuid;
-                    let player_uuid = Uuid::try_parse(player_id.as_str())
-                        .map_err(|e| {
-                            warn!("Error: Failed to convert from string to Uuid...");
-                            ErrorTypePlayerHandler::UuidParsingFailed(e.to_string())
-                        })?;
-                    // party.player_set_player_id(0, player_uuid)?;
-                    // party.set_active_player_uuid_player_map_and_component(player_query, player_uuid)?;
-                    party.init_main_player_uuid_player_map(player_query, player_uuid)?;
-                }
-                break;
+        let database_main_player = storage.main_player()?;
+        if let Some(database_main_player) = database_main_player { // A main player is already marked, sync the ecs Uuid to match locally stored profile 
+            let player_uuid = Uuid::try_parse(database_main_player.uuid.as_str())
+                .map_err(|e| {
+                    warn!("Error: Failed to convert from string to Uuid...");
+                    ErrorTypePlayerHandler::UuidParsingFailed(e.to_string())
+                })?;
+            party.init_main_player_uuid_player_map(commands, player_index, player_query, player_uuid)?;
+            plugin.set_main_player_uuid(&player_uuid)?;
+            return Ok(());
+        }
+
+        // Build the test reference player and adopt the main player spawned by start_up_protocol as the stored main player, together
+        let test_ref_info = self.test_ref_info(plugin)?;
+        let main_player_uuid = party.clone_main_player_uuid(player_query)?;
+        let main_player_email = plugin.get_main_player_email()?;
+        let main_player_username = plugin.get_main_player_username()?;
+        let (test_ref_inserted, main_player_inserted) = storage.with_transaction(|records| {
+            let test_ref_inserted = insert_test_ref_record(records, test_ref_info.as_ref())?;
+            let main_player_inserted = !records.player_exists(&main_player_uuid)?;
+            if main_player_inserted {
+                records.insert_player(&DBPlayer::new(&main_player_uuid, main_player_email, main_player_username, PlayerType::PlayerMain))?;
             }
+            records.set_main_player(&main_player_uuid)?;
+            Ok((test_ref_inserted, main_player_inserted))
+        })?;
+
+        if let Some(player_uuid) = test_ref_inserted {
+            party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                player_uuid,
+                player_type: PlayerType::PlayerTestRef,
+            }));
         }
+        if main_player_inserted {
+            party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                player_uuid: main_player_uuid,
+                player_type: PlayerType::PlayerMain,
+            }));
+        }
+        plugin.set_main_player_uuid(&main_player_uuid)?;
         Ok(())
     }
     
+    // Build the test reference player in the store
+    pub fn pipeline_db_and_party_startup_test_ref(
+        &self,
+        storage: &PlayerStorage,
+        party: &mut ResMut<Party>,
+        plugin: &BevyEasyPlayerHandlerPlugin,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let test_ref_info = self.test_ref_info(plugin)?;
+        if let Some(player_uuid) = storage.with_transaction(|records| insert_test_ref_record(records, test_ref_info.as_ref()))? {
+            party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                player_uuid,
+                player_type: PlayerType::PlayerTestRef,
+            }));
+        }
+        Ok(())
+    }
+
     pub fn pipeline_db_and_party_sync_main_player_uuids(
         &self,
-        db: &Res<DatabaseConnection>,
+        commands: &mut Commands,
+        storage: &PlayerStorage,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
         mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>
     ) -> Result<(), ErrorTypePlayerHandler> {
         let party_main_player_uuid = plugin.get_main_player_uuid()?;
@@ -388,7 +400,10 @@ impl PlayerHandlerInterface {
             return Err(ErrorTypePlayerHandler::PluginDataRetreivalFailed(format!("plugin.get_main_player_uuid()?; is None")))
         }
         let party_main_player_uuid = party_main_player_uuid.unwrap();
-        let database_main_player = self.query_db_main_player(&db)?;
+        let database_main_player = match storage.main_player()? {
+            Some(database_main_player) => database_main_player,
+            None => return Err(ErrorTypePlayerHandler::DBQueryFailed("Failed: to get main Player".to_string())),
+        };
         let database_main_player_uuid = match Uuid::try_parse(database_main_player.uuid.as_str()) {
             Ok(uuid) => uuid,
             Err(e) => {
@@ -398,67 +413,23 @@ impl PlayerHandlerInterface {
         };
         if party_main_player_uuid != &database_main_player_uuid {
             plugin.set_main_player_uuid(&database_main_player_uuid)?;
-            party.set_active_player_uuid_player_map_and_component(player_query, database_main_player_uuid)?;
+            party.set_active_player_uuid_player_map_and_component(commands, player_index, database_main_player_uuid)?;
         };
         Ok(())
     }
 
-    pub fn test_ref_info(&self) -> Result<(Uuid, String, String), ErrorTypePlayerHandler>  {
-        dotenv().ok();
-        // grab the test values from .env
-        let test_ref_uuid_string = match env::var("TEST_REF_PLAYER_UUID") {
-            Ok(value) => {
-                println!("[ dotenv ] TEST_REF_PLAYER_UUID: {}", value); 
-                value
-            },
-            Err(VarError::NotPresent) => {
-                return Err(ErrorTypePlayerHandler::VarErrorNotPresent)
-            },
-            Err(VarError::NotUnicode(err)) => {
-                let err_string = err.into_string().unwrap();
-                return Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err_string))
-            },
-        };
-
-        let test_ref_username_string = match env::var("TEST_REF_PLAYER_USERNAME") {
-            Ok(value) => {
-                println!("[ dotenv ] TEST_REF_PLAYER_USERNAME: {}", value); 
-                value
-            },
-            Err(VarError::NotPresent) => {
-                return Err(ErrorTypePlayerHandler::VarErrorNotPresent)
-            },
-            Err(VarError::NotUnicode(err)) => {
-                let err_string = err.into_string().unwrap();
-                return Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err_string))
-            },
-        };
-        
-        let test_ref_email_string = match env::var("TEST_REF_PLAYER_EMAIL") {
-            Ok(value) => {
-                println!("[ dotenv ] TEST_REF_PLAYER_EMAIL: {}", value); 
-                value
-            },
-            Err(VarError::NotPresent) => {
-                return Err(ErrorTypePlayerHandler::VarErrorNotPresent)
-            },
-            Err(VarError::NotUnicode(err)) => {
-                let err_string = err.into_string().unwrap();
-                return Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err_string))
-            },
-        };
-
-        let test_ref_uuid = match Uuid::try_parse(test_ref_uuid_string.as_str()){
-            Ok(value) => value,
-            Err(_) => return Err(ErrorTypePlayerHandler::UuidParsingFailed(format!("match Uuid::try_parse(test_ref_uuid_string.as_str())"))),
+    pub fn test_ref_info(
+        &self,
+        plugin: &BevyEasyPlayerHandlerPlugin,
+    ) -> Result<Option<(Uuid, String, String)>, ErrorTypePlayerHandler>  {
+        let test_ref_info = match plugin.get_test_ref_player_mode()? {
+            TestRefPlayerMode::Disabled => None,
+            TestRefPlayerMode::Enabled { uuid, username, email } => Some((
+                *uuid,
+                username.to_owned(),
+                email.to_owned(),
+            )),
         };
-
-        let test_ref_info = (
-            test_ref_uuid,
-            test_ref_username_string,
-            test_ref_email_string,
-        );
-
         Ok(test_ref_info)
     }
 }
\ No newline at end of file
@@ -5,14 +5,26 @@ use bevy_easy_shared_definitions::{
     ErrorTypePlayerHandler,
 };
 
-use rusqlite::Result;
+use rusqlite::{
+    types::Value,
+    OptionalExtension,
+    Result,
+};
 use uuid::Uuid;
 
 use crate::{
+    DBPartyPreset,
     DBPlayer,
+    DBSession,
     Party,
-    PlayerComponent,
+    PlayerData,
+    PlayerDbTx,
     PlayerHandlerInterface,
+    PlayerProfile,
+    PlayerStats,
+    PlayerStorage,
+    PlayerType,
+    ProfileValue,
 };
 
 impl PlayerHandlerInterface {    
@@ -45,92 +57,45 @@ impl PlayerHandlerInterface {
         &self,
         db: &Res<DatabaseConnection>,
     ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {    
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
-    
-        let mut stmt = conn
-            .prepare("SELECT uuid, email, username FROM player_table")
-            .map_err(|_| ErrorTypePlayerHandler::DBQueryFailed(format!("query_existing_players: Failed to get existing players...")))?; 
-        
-        let player_iter = stmt
-            .query_map([], |row| {
-                Ok(DBPlayer {
-                    uuid: row.get(0)?,
-                    email: row.get(1)?,
-                    username: row.get(2)?,
-                })
-            })
-            .map_err(|_| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_existing_players: Failed to map existing players...")))?;
-        
-        let mut players: Vec<DBPlayer> = Vec::new(); 
-        for player in player_iter {
-            players.push(player.unwrap());
-        }
-        Ok(players)
+        self.with_db_tx(db, |tx| tx.query_db_existing_players())
     }
 
     pub fn query_db_main_player(
         &self,
         db: &Res<DatabaseConnection>,
     ) -> Result<DBPlayer, ErrorTypePlayerHandler> {    
-        // Get and Lock the mutex to access the database connection
-        let conn = db.get_connection();
-        let conn = conn.lock();
-        let conn = match conn {
-            Ok(conn) => conn,
-            Err(_) => {
-                error!("Database connection lock poisoned.");
-                return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
-            }
-        };
+        self.with_db_tx(db, |tx| tx.query_db_main_player())
+    }
 
-        let mut stmt = conn
-            .prepare("SELECT uuid, email, username FROM player_table")
-            .map_err(|_| ErrorTypePlayerHandler::DBQueryFailed(format!("query_main_player: Failed to get existing players...")))?; 
-        
-        let player_iter = stmt
-            .query_map([], |row| {
-                Ok(DBPlayer {
-                    uuid: row.get(0)?,
-                    email: row.get(1)?,
-                    username: row.get(2)?,
-                })
-            })
-            .map_err(|_| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_main_player: Failed to map existing players...")))?;
-        
-        let mut main_player_container: Vec<DBPlayer> = Vec::new(); 
-        let mut idx: usize = 0; 
-        for player in player_iter {
-            if idx == 1 { // idx of 1 bypasses the test ref player created and returns the main.
-                main_player_container.push(player.unwrap());
-            }
-            idx += 1;
-        }
-        if let Some(main_player) = main_player_container.pop() {
-            Ok(main_player)
-        } else {
-            Err(ErrorTypePlayerHandler::DBQueryFailed(format!("Failed: to get main Player")))
-        }
+    pub fn query_db_main_player_exists(
+        &self,
+        db: &Res<DatabaseConnection>,
+    ) -> Result<bool, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_main_player_exists())
+    }
+
+    pub fn query_db_player_exists(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+    ) -> Result<bool, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_exists(player_uuid))
     }
     
+
     pub fn query_party_and_db_main_player_synced(
         &self,
-        db: &Res<DatabaseConnection>,
+        storage: &PlayerStorage,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_query: &Query<PlayerData>,
     ) -> Result<bool, ErrorTypePlayerHandler> {
         let mut result_synced = false;
         let party_size = party.get_player_count_party(player_query)?;
         if party_size > 0 {    
-            let database_main_player = self.query_db_main_player(&db)?;
+            let database_main_player = match storage.main_player()? {
+                Some(database_main_player) => database_main_player,
+                None => return Err(ErrorTypePlayerHandler::DBQueryFailed("Failed: to get main Player".to_string())),
+            };
             let party_main_player_uuid = party.clone_main_player_uuid(player_query)?;
             let database_main_player_uuid = match Uuid::try_parse(database_main_player.uuid.as_str()) {
                 Ok(uuid) => uuid,
@@ -145,6 +110,7 @@ impl PlayerHandlerInterface {
         Ok(result_synced)
     }
     
+
     pub fn query_db_table_player_exists(
         &self,
         db: &Res<DatabaseConnection>,
@@ -179,6 +145,7 @@ impl PlayerHandlerInterface {
         Ok(does_exist)
     }
     
+
     pub fn query_db_player_count_less_than_2(
         &self,
         db: &Res<DatabaseConnection>,
@@ -193,4 +160,363 @@ impl PlayerHandlerInterface {
     
         Ok(results)
     }
+
+    pub fn query_db_player_profile(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+    ) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_profile(player_uuid))
+    }
+
+    pub fn query_db_player_profile_value(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<Option<ProfileValue>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_profile_value(player_uuid, key))
+    }
+
+    // Typed read, a key holding another type is an error rather than None
+    pub fn query_db_player_profile_get<T: TryFrom<ProfileValue, Error = ProfileValue>>(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<Option<T>, ErrorTypePlayerHandler> {
+        match self.query_db_player_profile_value(db, player_uuid, key)? {
+            Some(value) => T::try_from(value)
+                .map(Some)
+                .map_err(|value| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_player_profile_get [{}] failed: stored value is [{:?}]", key, value))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn query_db_player_stat(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+        stat: &str,
+    ) -> Result<Option<i64>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_stat(player_uuid, stat))
+    }
+
+    pub fn query_db_player_stats(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+    ) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_stats(player_uuid))
+    }
+
+    pub fn query_db_top_players_by_stat(
+        &self,
+        db: &Res<DatabaseConnection>,
+        stat: &str,
+        limit: usize,
+    ) -> Result<Vec<(Uuid, i64)>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_top_players_by_stat(stat, limit))
+    }
+
+    pub fn query_db_recent_players(
+        &self,
+        db: &Res<DatabaseConnection>,
+        limit: usize,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_recent_players(limit))
+    }
+
+    pub fn query_db_recent_sessions(
+        &self,
+        db: &Res<DatabaseConnection>,
+        limit: usize,
+    ) -> Result<Vec<DBSession>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_recent_sessions(limit))
+    }
+
+    pub fn query_db_player_timestamps(
+        &self,
+        db: &Res<DatabaseConnection>,
+        player_uuid: &Uuid,
+    ) -> Result<Option<(i64, Option<i64>)>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_player_timestamps(player_uuid))
+    }
+
+    pub fn query_db_local_profiles(
+        &self,
+        db: &Res<DatabaseConnection>,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_local_profiles())
+    }
+
+    pub fn query_db_party_presets(
+        &self,
+        db: &Res<DatabaseConnection>,
+    ) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.with_db_tx(db, |tx| tx.query_db_party_presets())
+    }
+
+    pub fn query_db_party_preset(
+        &self,
+        db: &Res<DatabaseConnection>,
+        name: &str,
+    ) -> Result<Option<DBPartyPreset>, ErrorTypePlayerHandler> {
+        let presets = self.query_db_party_presets(db)?;
+        Ok(presets.into_iter().find(|preset| preset.name == name))
+    }
+}
+
+// Profile, stats, session and preset reads, so they can run inside the same transaction as the writes
+impl<'conn> PlayerDbTx<'conn> {
+    pub fn query_db_player_profile(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT key, value_type, value FROM player_profile WHERE uuid = ?1")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_profile: prepare failed Error: [{}]", e)))?;
+        let rows = stmt
+            .query_map([String::from(*player_uuid)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, Value>(2)?))
+            })
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_profile failed Error: [{}]", e)))?;
+
+        let mut profile = PlayerProfile::new();
+        for row in rows {
+            let (key, value_type, value) = row
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_player_profile: row mapping failed Error: [{}]", e)))?;
+            profile.set(&key, ProfileValue::from_db(value_type, value)?);
+        }
+
+        Ok(profile)
+    }
+
+    pub fn query_db_player_profile_value(
+        &self,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<Option<ProfileValue>, ErrorTypePlayerHandler> {
+        let stored = self.tx.query_row(
+            "SELECT value_type, value FROM player_profile WHERE uuid = ?1 AND key = ?2",
+            (String::from(*player_uuid), key),
+            |row| Ok((row.get::<_, i32>(0)?, row.get::<_, Value>(1)?)),
+        )
+        .optional()
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_profile_value [{}] failed Error: [{}]", key, e)))?;
+
+        match stored {
+            Some((value_type, value)) => Ok(Some(ProfileValue::from_db(value_type, value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn query_db_player_stat(
+        &self,
+        player_uuid: &Uuid,
+        stat: &str,
+    ) -> Result<Option<i64>, ErrorTypePlayerHandler> {
+        let value: Option<i64> = self.tx.query_row(
+            "SELECT value FROM player_stats WHERE uuid = ?1 AND stat = ?2",
+            (String::from(*player_uuid), stat),
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_stat [{}] failed Error: [{}]", stat, e)))?;
+
+        Ok(value)
+    }
+
+    pub fn query_db_player_stats(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT stat, value FROM player_stats WHERE uuid = ?1")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_stats: prepare failed Error: [{}]", e)))?;
+        let values = stmt
+            .query_map([String::from(*player_uuid)], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_stats failed Error: [{}]", e)))?
+            .collect::<Result<Vec<(String, i64)>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_player_stats: row mapping failed Error: [{}]", e)))?;
+
+        Ok(PlayerStats::from_values(values))
+    }
+
+    // Highest values first, ties go to the older player record
+    pub fn query_db_top_players_by_stat(
+        &self,
+        stat: &str,
+        limit: usize,
+    ) -> Result<Vec<(Uuid, i64)>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare(
+                "SELECT player_stats.uuid, player_stats.value FROM player_stats
+                    JOIN player_table ON player_table.uuid = player_stats.uuid
+                    WHERE player_stats.stat = ?1
+                    ORDER BY player_stats.value DESC, player_table.rowid
+                    LIMIT ?2",
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_top_players_by_stat: prepare failed Error: [{}]", e)))?;
+        let rows = stmt
+            .query_map((stat, limit as i64), |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_top_players_by_stat [{}] failed Error: [{}]", stat, e)))?;
+
+        let mut top_players: Vec<(Uuid, i64)> = Vec::new();
+        for row in rows {
+            let (player_uuid, value) = row
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_top_players_by_stat: row mapping failed Error: [{}]", e)))?;
+            let player_uuid = Uuid::try_parse(&player_uuid)
+                .map_err(|e| ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))?;
+            top_players.push((player_uuid, value));
+        }
+
+        Ok(top_players)
+    }
+
+    // Most recently seen first, the test reference record is left out
+    pub fn query_db_recent_players(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare(
+                "SELECT uuid, email, username, player_type FROM player_table
+                    WHERE player_type != ?1
+                    ORDER BY last_seen_at DESC, rowid DESC
+                    LIMIT ?2",
+            )
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_players: prepare failed Error: [{}]", e)))?;
+        let players = stmt
+            .query_map((PlayerType::PlayerTestRef.to_db_code(), limit as i64), |row| {
+                Ok(DBPlayer {
+                    uuid: row.get(0)?,
+                    email: row.get(1)?,
+                    username: row.get(2)?,
+                    player_type: row.get(3)?,
+                })
+            })
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_players failed Error: [{}]", e)))?
+            .collect::<Result<Vec<DBPlayer>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_recent_players: row mapping failed Error: [{}]", e)))?;
+
+        Ok(players)
+    }
+
+    // Newest first, each with its players in seat order
+    pub fn query_db_recent_sessions(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<DBSession>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT session_id, started_at, ended_at FROM session_table ORDER BY session_id DESC LIMIT ?1")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_sessions: prepare failed Error: [{}]", e)))?;
+        let mut sessions = stmt
+            .query_map([limit as i64], |row| {
+                Ok(DBSession {
+                    session_id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    ended_at: row.get(2)?,
+                    players: Vec::new(),
+                })
+            })
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_sessions failed Error: [{}]", e)))?
+            .collect::<Result<Vec<DBSession>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_recent_sessions: row mapping failed Error: [{}]", e)))?;
+
+        let mut stmt = self.tx
+            .prepare("SELECT seat, uuid FROM session_players WHERE session_id = ?1 ORDER BY seat, rowid")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_sessions: prepare players failed Error: [{}]", e)))?;
+        for session in sessions.iter_mut() {
+            let players = stmt
+                .query_map([session.session_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_recent_sessions: players of [{}] failed Error: [{}]", session.session_id, e)))?
+                .collect::<Result<Vec<(i64, String)>>>()
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_recent_sessions: player mapping failed Error: [{}]", e)))?;
+            for (seat, player_uuid) in players {
+                let player_uuid = Uuid::try_parse(&player_uuid)
+                    .map_err(|e| ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))?;
+                session.players.push((seat as usize, player_uuid));
+            }
+        }
+
+        Ok(sessions)
+    }
+
+    // created_at and last_seen_at, last_seen_at stays None for records no session has seen yet
+    pub fn query_db_player_timestamps(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Result<Option<(i64, Option<i64>)>, ErrorTypePlayerHandler> {
+        let timestamps = self.tx.query_row(
+            "SELECT created_at, last_seen_at FROM player_table WHERE uuid = ?1",
+            [String::from(*player_uuid)],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<i64>>(1)?)),
+        )
+        .optional()
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_timestamps failed Error: [{}]", e)))?;
+
+        Ok(timestamps.map(|(created_at, last_seen_at)| (created_at.unwrap_or(0), last_seen_at)))
+    }
+
+    // Every PlayerMain record, any of them can be picked as the main player with select_profile
+    pub fn query_db_local_profiles(
+        &self,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT uuid, email, username, player_type FROM player_table WHERE player_type = ?1 ORDER BY rowid")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_local_profiles: prepare failed Error: [{}]", e)))?;
+        let profiles = stmt
+            .query_map([PlayerType::PlayerMain.to_db_code()], |row| {
+                Ok(DBPlayer {
+                    uuid: row.get(0)?,
+                    email: row.get(1)?,
+                    username: row.get(2)?,
+                    player_type: row.get(3)?,
+                })
+            })
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_local_profiles failed Error: [{}]", e)))?
+            .collect::<Result<Vec<DBPlayer>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_local_profiles: row mapping failed Error: [{}]", e)))?;
+
+        Ok(profiles)
+    }
+
+    // Sorted by name, each with its members in seat order
+    pub fn query_db_party_presets(
+        &self,
+    ) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT name, created_at FROM party_preset ORDER BY name")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_party_presets: prepare failed Error: [{}]", e)))?;
+        let mut presets = stmt
+            .query_map([], |row| {
+                Ok(DBPartyPreset {
+                    name: row.get(0)?,
+                    created_at: row.get(1)?,
+                    members: Vec::new(),
+                })
+            })
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_party_presets failed Error: [{}]", e)))?
+            .collect::<Result<Vec<DBPartyPreset>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_party_presets: row mapping failed Error: [{}]", e)))?;
+
+        let mut stmt = self.tx
+            .prepare("SELECT seat, uuid FROM party_preset_member WHERE name = ?1 ORDER BY seat")
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_party_presets: prepare members failed Error: [{}]", e)))?;
+        for preset in presets.iter_mut() {
+            let members = stmt
+                .query_map([&preset.name], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_party_presets: members of [{}] failed Error: [{}]", preset.name, e)))?
+                .collect::<Result<Vec<(i64, String)>>>()
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_db_party_presets: member mapping failed Error: [{}]", e)))?;
+            for (seat, player_uuid) in members {
+                let player_uuid = Uuid::try_parse(&player_uuid)
+                    .map_err(|e| ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))?;
+                preset.members.push((seat as usize, player_uuid));
+            }
+        }
+
+        Ok(presets)
+    }
 }
\ No newline at end of file
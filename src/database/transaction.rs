@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::{
+    DatabaseConnection,
+    ErrorTypePlayerHandler,
+};
+
+use rusqlite::{
+    Connection,
+    OptionalExtension,
+    Result,
+};
+use std::panic::{
+    self,
+    AssertUnwindSafe,
+};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    handlers::session::unix_timestamp_now,
+    DBPlayer,
+    PlayerDbTx,
+    PlayerHandlerInterface,
+    PlayerType,
+};
+
+impl PlayerHandlerInterface {
+    // Takes the lock once and runs everything f does in one transaction. Ok commits, an Err rolls the whole unit back.
+    // A panic in f is rolled back as well and carries on once the lock is released, so it does not poison the connection
+    pub fn with_db_tx<T>(
+        &self,
+        db: &Res<DatabaseConnection>,
+        f: impl FnOnce(&mut PlayerDbTx) -> Result<T, ErrorTypePlayerHandler>,
+    ) -> Result<T, ErrorTypePlayerHandler> {
+        run_db_tx(&db.get_connection(), f)
+    }
+}
+
+// with_db_tx for callers that hold the connection rather than the resource, such as SqlitePlayerStore
+pub(crate) fn run_db_tx<T>(
+    conn: &Mutex<Connection>,
+    f: impl FnOnce(&mut PlayerDbTx) -> Result<T, ErrorTypePlayerHandler>,
+) -> Result<T, ErrorTypePlayerHandler> {
+    // Lock the mutex to access the database connection
+    let mut conn = match conn.lock() {
+        Ok(conn) => conn,
+        Err(_) => {
+            error!("Database connection lock poisoned.");
+            return Err(ErrorTypePlayerHandler::DatabaseLockPoisoned);
+        }
+    };
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("with_db_tx: begin failed Error: [{}]", e)))?;
+    let mut db_tx = PlayerDbTx { tx };
+    // Dropping db_tx without a commit rolls it back
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| f(&mut db_tx))) {
+        Ok(result) => result?,
+        Err(payload) => {
+            drop(db_tx);
+            drop(conn);
+            panic::resume_unwind(payload);
+        },
+    };
+    db_tx.tx
+        .commit()
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("with_db_tx: commit failed Error: [{}]", e)))?;
+
+    Ok(result)
+}
+
+// The player_table actions and queries the multi-step pipelines share, the PlayerHandlerInterface versions wrap each one in its own transaction
+impl<'conn> PlayerDbTx<'conn> {
+    pub fn action_count_players_in_db(
+        &self,
+    ) -> Result<i32, ErrorTypePlayerHandler> {
+        let party_player_count_query: i32 = self.tx.query_row(
+            "SELECT COUNT(*) AS PartyPlayerCount FROM player_table;",
+            (),
+            |row| row.get(0),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("Player Count failed, Error: [{}]", e)))?;
+
+        Ok(party_player_count_query)
+    }
+
+    pub fn action_insert_player_record(
+        &self,
+        main_player_uuid: &Uuid,
+        main_player_email: Option<&String>,
+        main_player_username: Option<&String>,
+        player_type: PlayerType,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let insert_target = String::from(*main_player_uuid);
+        self.tx.execute(
+            "INSERT INTO player_table (uuid, email, username, player_type, created_at, last_seen_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            (insert_target, main_player_email, main_player_username, &player_type, unix_timestamp_now()),
+        )
+            .map_err(|e| match player_type {
+                PlayerType::PlayerAiLocal => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Ai into 'player_table' failed Error: [{}]", e)),
+                PlayerType::PlayerAiRemote => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Remote Player Ai into 'player_table' failed Error: [{}]", e)),
+                PlayerType::PlayerLocal => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Local into 'player_table' failed Error: [{}]", e)),
+                PlayerType::PlayerMain => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Main into 'player_table' failed Error: [{}]", e)),
+                PlayerType::PlayerRemote => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Remote Local into 'player_table' failed Error: [{}]", e)),
+                PlayerType::PlayerTestRef => ErrorTypePlayerHandler::DBActionFailed(format!("Action Insert Record Player Test Reference Local into 'player_table' failed Error: [{}]", e)),
+            })?;
+        Ok(())
+    }
+
+    // Inserts the configured test reference record unless it is already stored, returns its uuid when it was inserted
+    pub fn action_insert_test_ref_record(
+        &self,
+        test_ref_info: Option<&(Uuid, String, String)>,
+    ) -> Result<Option<Uuid>, ErrorTypePlayerHandler> {
+        let Some((test_ref_uuid, test_ref_username, test_ref_email)) = test_ref_info else {
+            return Ok(None);
+        };
+        if self.query_db_player_exists(test_ref_uuid)? {
+            return Ok(None);
+        }
+        self.action_insert_player_record(test_ref_uuid, Some(test_ref_email), Some(test_ref_username), PlayerType::PlayerTestRef)?;
+        Ok(Some(*test_ref_uuid))
+    }
+
+    // Clear and set share the transaction, so the table is never left without a main player
+    pub fn action_set_main_player_record(
+        &self,
+        main_player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "UPDATE player_table SET is_main = 0 WHERE is_main = 1",
+            (),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_set_main_player_record: clear failed Error: [{}]", e)))?;
+        let updated = self.tx.execute(
+            "UPDATE player_table SET is_main = 1 WHERE uuid = ?1",
+            [String::from(*main_player_uuid)],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_set_main_player_record: set failed Error: [{}]", e)))?;
+        if updated != 1 {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("action_set_main_player_record failed: no record for [{}]", main_player_uuid)));
+        }
+
+        Ok(())
+    }
+
+    // Removes one record with its stats and profile, the party is left alone
+    pub fn action_remove_player_record(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "DELETE FROM player_stats WHERE uuid = ?1",
+            [String::from(*player_uuid)],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_player_record: clearing player_stats failed Error: [{}]", e)))?;
+        self.tx.execute(
+            "DELETE FROM player_profile WHERE uuid = ?1",
+            [String::from(*player_uuid)],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_player_record: clearing player_profile failed Error: [{}]", e)))?;
+        let deleted = self.tx.execute(
+            "DELETE FROM player_table WHERE uuid = ?1",
+            [String::from(*player_uuid)],
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_player_record failed Error: [{}]", e)))?;
+        if deleted == 0 {
+            return Err(ErrorTypePlayerHandler::DBQueryFailed(format!("action_remove_player_record failed: no record for [{}]", player_uuid)));
+        }
+
+        Ok(())
+    }
+
+    pub fn action_remove_all_player_records(
+        &self,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.tx.execute(
+            "DELETE FROM player_stats",
+            (),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_all_player_records: clearing player_stats failed Error: [{}]", e)))?;
+        self.tx.execute(
+            "DELETE FROM player_profile",
+            (),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_all_player_records: clearing player_profile failed Error: [{}]", e)))?;
+        self.tx.execute(
+            "DELETE FROM player_table",
+            (),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("action_remove_all_player_records failed Error: [{}]", e)))?;
+
+        Ok(())
+    }
+
+    pub fn query_db_existing_players(
+        &self,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        let mut stmt = self.tx
+            .prepare("SELECT uuid, email, username, player_type FROM player_table")
+            .map_err(|_| ErrorTypePlayerHandler::DBQueryFailed("query_existing_players: Failed to get existing players...".to_string()))?;
+        let players = stmt
+            .query_map([], |row| {
+                Ok(DBPlayer {
+                    uuid: row.get(0)?,
+                    email: row.get(1)?,
+                    username: row.get(2)?,
+                    player_type: row.get(3)?,
+                })
+            })
+            .map_err(|_| ErrorTypePlayerHandler::DBQueryMappingFailed("query_existing_players: Failed to map existing players...".to_string()))?
+            .collect::<Result<Vec<DBPlayer>>>()
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("query_existing_players: row mapping failed Error: [{}]", e)))?;
+
+        Ok(players)
+    }
+
+    pub fn query_db_main_player(
+        &self,
+    ) -> Result<DBPlayer, ErrorTypePlayerHandler> {
+        let main_player = self.tx
+            .query_row(
+                "SELECT uuid, email, username, player_type FROM player_table WHERE is_main = 1",
+                [],
+                |row| {
+                    Ok(DBPlayer {
+                        uuid: row.get(0)?,
+                        email: row.get(1)?,
+                        username: row.get(2)?,
+                        player_type: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|_| ErrorTypePlayerHandler::DBQueryMappingFailed("query_main_player: Failed to map main player...".to_string()))?;
+
+        match main_player {
+            Some(main_player) => Ok(main_player),
+            None => Err(ErrorTypePlayerHandler::DBQueryFailed("Failed: to get main Player".to_string())),
+        }
+    }
+
+    pub fn query_db_main_player_exists(
+        &self,
+    ) -> Result<bool, ErrorTypePlayerHandler> {
+        let does_exist: bool = self.tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM player_table WHERE is_main = 1)",
+            (),
+            |row| row.get::<_, i32>(0),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_main_player_exists failed Error: [{}]", e)))?
+        == 1;
+
+        Ok(does_exist)
+    }
+
+    pub fn query_db_player_exists(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Result<bool, ErrorTypePlayerHandler> {
+        let does_exist: bool = self.tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM player_table WHERE uuid = ?1)",
+            [String::from(*player_uuid)],
+            |row| row.get::<_, i32>(0),
+        )
+        .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("query_db_player_exists failed Error: [{}]", e)))?
+        == 1;
+
+        Ok(does_exist)
+    }
+}
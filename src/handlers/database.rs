@@ -4,19 +4,31 @@ use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
 
 use crate::{
     BevyEasyPlayerHandlerPlugin, 
-    DatabaseConnection,
     DBPlayer, 
     Party, 
     Player,
-    PlayerComponent,
-    PlayerMain,
+    PlayerBundle,
+    PlayerData,
     PlayerType,
-    PlayerHandlerInterface, 
+    PlayerHandlerInterface,
+    PlayerHandlerStartupError,
+    PlayerHandlerState,
+    PlayerIndex, 
+    PlayerStorage,
 };
 
-use std::sync::Arc;
-use std::sync::Mutex;
-use rusqlite::Result;
+use rusqlite::{
+    types::{
+        FromSql,
+        FromSqlError,
+        FromSqlResult,
+        ToSql,
+        ToSqlOutput,
+        ValueRef,
+    },
+    Result,
+};
+use uuid::Uuid;
 
 impl PlayerHandlerInterface {
     pub fn get() -> Self {
@@ -25,63 +37,93 @@ impl PlayerHandlerInterface {
 
     pub fn start_up_protocol(
         mut commands: Commands,
-        db: Res<DatabaseConnection>,
+        storage: Res<PlayerStorage>,
+        mut party: ResMut<Party>,
         phi: ResMut<PlayerHandlerInterface>,
         plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
+        mut startup_error: ResMut<PlayerHandlerStartupError>,
+        mut next_state: ResMut<NextState<PlayerHandlerState>>,
     ) {    
-        let player_table_exists = phi.query_db_table_player_exists(&db).unwrap();
-        if !player_table_exists {
-            phi.action_table_player_init(&db).unwrap();
+        // ----- [ Migrate and read the store ] ----- //
+
+        if let Err(e) = storage.load() {
+            error!("start_up_protocol -> storage.load [ Failed ] Error: {:?}", e);
+            startup_error.error = Some(e);
+            next_state.set(PlayerHandlerState::Failed);
+            return;
         }
 
-        // ----- [ Build main player ] ----- //
+        // ----- [ Leave the main player to select_profile ] ----- //
 
-        let player_email = plugin.main_player_email.clone().unwrap();
-        let player_username = plugin.main_player_username.clone().unwrap();
+        if plugin.profile_selection {
+            if let Err(e) = phi.pipeline_db_and_party_startup_test_ref(&storage, &mut party, &plugin) {
+                error!("start_up_protocol -> pipeline_db_and_party_startup_test_ref [ Failed ] Error: {:?}", e);
+                startup_error.error = Some(e.into());
+                next_state.set(PlayerHandlerState::Failed);
+            }
+            return;
+        }
 
-        let player_component = PlayerMain::new(
-            Some(player_email), 
-            Some(player_username),
+        // ----- [ Build main player ] ----- //
+
+        let player_bundle = PlayerBundle::new(
+            plugin.main_player_email.clone(), 
+            plugin.main_player_username.clone(),
             None,
             PlayerType::PlayerMain,
         );
 
-        let player: Arc<Mutex<dyn Player + Send>> = Arc::new(Mutex::new(player_component));
-        commands.spawn(PlayerComponent{player: player});
+        commands.spawn(player_bundle);
     }
 
+    // Ready once the main player is stored, seated and synced, Failed with the error otherwise
     pub fn start_up_protocol_finish(
         mut commands: Commands,
-        db: Res<DatabaseConnection>,
-        entity_player_query: Query<(Entity, &PlayerComponent)>, 
+        storage: Res<PlayerStorage>,
         mut party: ResMut<Party>,
         phi: ResMut<PlayerHandlerInterface>,
-        player_query: Query<&PlayerComponent>,
-        mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
+        player_index: Res<PlayerIndex>,
+        player_query: Query<PlayerData>,
+        plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
+        mut startup_error: ResMut<PlayerHandlerStartupError>,
+        mut next_state: ResMut<NextState<PlayerHandlerState>>,
     ) {
+        match phi.pipeline_start_up_protocol_finish(&mut commands, &storage, &mut party, &player_index, &player_query, plugin) {
+            Ok(()) => next_state.set(PlayerHandlerState::Ready),
+            Err(e) => {
+                error!("start_up_protocol_finish [ Failed ] Error: {:?}", e);
+                startup_error.error = Some(e.into());
+                next_state.set(PlayerHandlerState::Failed);
+            },
+        }
+    }
+
+    fn pipeline_start_up_protocol_finish(
+        &self,
+        commands: &mut Commands,
+        storage: &PlayerStorage,
+        party: &mut ResMut<Party>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
+        mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
+    ) -> Result<(), ErrorTypePlayerHandler> {
         // ----- [ Vertify database test ref and main player exists ] ----- //
     
-        let players_test_ref_and_owner_exists = phi.query_db_player_count_less_than_2(&db).unwrap();
-        
-        if !players_test_ref_and_owner_exists {
-            phi.pipeline_db_and_party_startup_test_ref_and_init_main_player(&db, &mut commands, &entity_player_query, &mut party, &player_query, &mut plugin).unwrap();
+        if storage.main_player()?.is_none() {
+            self.pipeline_db_and_party_startup_test_ref_and_init_main_player(commands, storage, party, player_index, player_query, &mut plugin)?;
         }
     
         // ----- [ Sync party and database main players uuid ] ----- //
     
-        let party_and_database_main_player_synced = phi.query_party_and_db_main_player_synced(&db, &mut party, &player_query).unwrap();
-        if !party_and_database_main_player_synced {
-            let player_mutex = player_query.single().player.lock().unwrap();
-            let player_id = player_mutex.get_player_id().unwrap().clone();
-            drop(player_mutex);
-            party.player_map.insert(1, player_id);
-            match phi.pipeline_db_and_party_sync_main_player_uuids(&db, &mut party, &player_query, plugin) {
-                Ok(sync) => sync,
-                Err(e) => {
-                    warn!("start_up_protocol_finish -> pipeline_db_and_party_sync_main_player_uuids [ Failed ] Error: {:?}", e);
-                }, 
-            };
+        if !self.query_party_and_db_main_player_synced(storage, party, player_query)? {
+            let player_id = player_query
+                .get_single()
+                .map_err(|e| ErrorTypePlayerHandler::PartyActionFailed(format!("start_up_protocol_finish: expected only the main player Error: [{}]", e)))?
+                .id.0;
+            party.player_map.insert(1, player_id, 1)?;
+            self.pipeline_db_and_party_sync_main_player_uuids(commands, storage, party, player_index, plugin)?;
         }
+        Ok(())
     }
 
     // --- Internal Helper Functions --- //
@@ -90,7 +132,7 @@ impl PlayerHandlerInterface {
         &self,
         plugin: &ResMut<BevyEasyPlayerHandlerPlugin>,
         party: &mut ResMut<Party>,
-        player_query: &Query<&PlayerComponent>,
+        player_query: &Query<PlayerData>,
     ) -> Result<(), ErrorTypePlayerHandler> {
         let party_size_limit = plugin.party_size.unwrap() as usize;
         let party_size = party.get_player_count_party(&player_query)?;
@@ -105,6 +147,21 @@ impl PlayerHandlerInterface {
 }
 
 impl DBPlayer {
+    // A missing email or username is stored as an empty string
+    pub fn new(
+        player_uuid: &Uuid,
+        email: Option<&String>,
+        username: Option<&String>,
+        player_type: PlayerType,
+    ) -> Self {
+        DBPlayer {
+            uuid: player_uuid.to_string(),
+            email: email.cloned().unwrap_or_default(),
+            username: username.cloned().unwrap_or_default(),
+            player_type,
+        }
+    }
+
     pub fn get_uuid_string(&self) -> &String {
         &self.uuid
     }
@@ -114,4 +171,46 @@ impl DBPlayer {
     pub fn get_username_string(&self) -> &String {
         &self.username
     }
+    pub fn get_player_type(&self) -> &PlayerType {
+        &self.player_type
+    }
+}
+
+// The codes below are persisted in player_table.player_type, never renumber an existing entry.
+impl PlayerType {
+    pub fn to_db_code(&self) -> i32 {
+        match self {
+            PlayerType::PlayerAiLocal => 1,
+            PlayerType::PlayerAiRemote => 2,
+            PlayerType::PlayerLocal => 3,
+            PlayerType::PlayerMain => 4,
+            PlayerType::PlayerRemote => 5,
+            PlayerType::PlayerTestRef => 6,
+        }
+    }
+
+    pub fn from_db_code(code: i32) -> Result<PlayerType, ErrorTypePlayerHandler> {
+        match code {
+            1 => Ok(PlayerType::PlayerAiLocal),
+            2 => Ok(PlayerType::PlayerAiRemote),
+            3 => Ok(PlayerType::PlayerLocal),
+            4 => Ok(PlayerType::PlayerMain),
+            5 => Ok(PlayerType::PlayerRemote),
+            6 => Ok(PlayerType::PlayerTestRef),
+            _ => Err(ErrorTypePlayerHandler::DBQueryMappingFailed(format!("PlayerType::from_db_code failed: unknown player_type code [{}]", code))),
+        }
+    }
+}
+
+impl ToSql for PlayerType {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_db_code()))
+    }
+}
+
+impl FromSql for PlayerType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let code = i32::column_result(value)?;
+        PlayerType::from_db_code(code).map_err(|_| FromSqlError::OutOfRange(code as i64))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::{
+    ActivePlayerChanged,
+    BevyEasyPlayerHandlerPlugin,
+    Party,
+    PartyChange,
+    PartyDelta,
+    PartyEvent,
+    PartyReordered,
+    Player,
+    PlayerBundle,
+    PlayerIndex,
+    PlayerLeftParty,
+    PlayerName,
+};
+
+impl Party {
+    pub fn get_revision(
+        &self,
+    ) -> u64 {
+        self.revision
+    }
+
+    // Stamps the change with the next revision and logs it, returns that revision
+    pub fn record_delta(
+        &mut self,
+        change: PartyChange,
+    ) -> u64 {
+        self.revision += 1;
+        self.delta_log.push(PartyDelta {
+            revision: self.revision,
+            change,
+        });
+        self.revision
+    }
+
+    pub fn deltas_since(
+        &self,
+        revision: u64,
+    ) -> Vec<PartyDelta> {
+        self.delta_log
+            .iter()
+            .filter(|delta| delta.revision > revision)
+            .cloned()
+            .collect()
+    }
+
+    pub fn drain_deltas(
+        &mut self,
+    ) -> Vec<PartyDelta> {
+        std::mem::take(&mut self.delta_log)
+    }
+
+    // Replays a delta from another party. Only the revision right after ours is accepted, and a change that does not fit the roster or the seating mode is refused before anything moves
+    pub fn apply_delta(
+        &mut self,
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        plugin: &BevyEasyPlayerHandlerPlugin,
+        delta: &PartyDelta,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if delta.revision <= self.revision {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: revision [{}] is already applied, party is at [{}]", delta.revision, self.revision)))
+        }
+        if delta.revision != self.revision + 1 {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: revision [{}] skips ahead of [{}], a delta is missing", delta.revision, self.revision)))
+        }
+
+        match &delta.change {
+            PartyChange::Joined { player_uuid, seat, player_type, username } => {
+                if *seat == 0 || self.player_map.get(seat).is_some() || self.player_map.contains_uuid(player_uuid) {
+                    return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: [{}] can not join seat [{}]", player_uuid, seat)))
+                }
+                // on_player_component_spawned finds the player already seated and sends PlayerJoinedParty
+                let seat_limit = plugin.get_seat_limit(&self.player_map)?;
+                self.player_map.insert(*seat, *player_uuid, seat_limit)?;
+                if player_index.get_entity(player_uuid).is_none() {
+                    commands.spawn(PlayerBundle::new(None, username.clone(), Some(*player_uuid), player_type.clone()));
+                }
+            },
+            PartyChange::Left { player_uuid, seat } => {
+                if self.player_map.get(seat) != Some(player_uuid) {
+                    return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: [{}] is not in seat [{}]", player_uuid, seat)))
+                }
+                self.player_map.take(seat);
+                if let Some(entity) = player_index.get_entity(player_uuid) {
+                    commands.entity(entity).despawn_recursive();
+                }
+                self.pending_events.push(PartyEvent::PlayerLeftParty(PlayerLeftParty {
+                    player_uuid: *player_uuid,
+                    party_index: *seat,
+                }));
+            },
+            PartyChange::Renamed { player_uuid, username } => {
+                let entity = match player_index.get_entity(player_uuid) {
+                    Some(entity) => entity,
+                    None => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: [{}] has no entity to rename", player_uuid))),
+                };
+                commands.entity(entity).insert(PlayerName(username.clone()));
+            },
+            PartyChange::Reordered { player_map } => {
+                let seated: HashSet<&Uuid> = self.player_map.iter().map(|(_, player_uuid)| player_uuid).collect();
+                let reordered: HashSet<&Uuid> = player_map.iter().map(|(_, player_uuid)| player_uuid).collect();
+                if seated != reordered {
+                    return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: reorder at revision [{}] seats different players", delta.revision)))
+                }
+                self.player_map = player_map.clone();
+                self.pending_events.push(PartyEvent::PartyReordered(PartyReordered {
+                    player_map: player_map.clone(),
+                }));
+            },
+            PartyChange::ActiveChanged { active_player } => {
+                if !self.player_map.can_be_active(active_player) {
+                    return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_delta failed: active seat [{}] is not a seated seat", active_player)))
+                }
+                let previous_index = self.active_player;
+                self.active_player = *active_player;
+                self.pending_events.push(PartyEvent::ActivePlayerChanged(ActivePlayerChanged {
+                    previous_index,
+                    active_index: *active_player,
+                    player_uuid: self.player_map.get(active_player).copied(),
+                }));
+            },
+        }
+
+        // Logged as is, so this party can pass the same revisions on
+        self.revision = delta.revision;
+        self.delta_log.push(delta.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use uuid::Uuid;
+
+use crate::{
+    KeyboardLayout,
+    Party,
+    PlayerHandler,
+    PlayerId,
+    PlayerIndex,
+    PlayerInputBinding,
+    PlayerInputHandler,
+};
+
+impl<'w, 's> PlayerInputHandler<'w, 's> {
+    pub fn bind_gamepad(
+        &mut self,
+        player_uuid: &Uuid,
+        gamepad: Gamepad,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let PlayerHandler { commands, party, player_index, .. } = &mut self.handler;
+        party.bind_input(commands, player_index, &self.input_binding_query, player_uuid, PlayerInputBinding::Gamepad(gamepad))
+    }
+
+    pub fn bind_keyboard(
+        &mut self,
+        player_uuid: &Uuid,
+        layout: KeyboardLayout,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let PlayerHandler { commands, party, player_index, .. } = &mut self.handler;
+        party.bind_input(commands, player_index, &self.input_binding_query, player_uuid, PlayerInputBinding::Keyboard(layout))
+    }
+
+    pub fn unbind_input(
+        &mut self,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let PlayerHandler { commands, party, player_index, .. } = &mut self.handler;
+        party.unbind_input(commands, player_index, player_uuid)
+    }
+}
+
+impl Party {
+    pub fn bind_input(
+        &self,
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        input_binding_query: &Query<(&PlayerId, &PlayerInputBinding)>,
+        player_uuid: &Uuid,
+        binding: PlayerInputBinding,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if !self.player_map.contains_uuid(player_uuid) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("bind_input failed: player [{}] is not in the party", player_uuid)))
+        }
+        let entity = match player_index.get_entity(player_uuid) {
+            Some(entity) => entity,
+            None => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("bind_input failed: player [{}] has no entity", player_uuid))),
+        };
+        if let Some(bound_uuid) = self.get_player_by_input_binding(input_binding_query, &binding) {
+            if &bound_uuid != player_uuid {
+                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("bind_input failed: [{:?}] already drives player [{}]", binding, bound_uuid)))
+            }
+        }
+        commands.entity(entity).insert(binding);
+        Ok(())
+    }
+
+    pub fn unbind_input(
+        &self,
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let entity = match player_index.get_entity(player_uuid) {
+            Some(entity) => entity,
+            None => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("unbind_input failed: player [{}] has no entity", player_uuid))),
+        };
+        commands.entity(entity).remove::<PlayerInputBinding>();
+        Ok(())
+    }
+
+    pub fn get_player_by_input_binding(
+        &self,
+        input_binding_query: &Query<(&PlayerId, &PlayerInputBinding)>,
+        binding: &PlayerInputBinding,
+    ) -> Option<Uuid> {
+        input_binding_query
+            .iter()
+            .find(|(_, player_binding)| *player_binding == binding)
+            .map(|(player_id, _)| player_id.0)
+    }
+}
+
+// Players who left the party give their device back
+pub fn release_input_bindings(
+    mut commands: Commands,
+    party: Res<Party>,
+    binding_query: Query<(Entity, &PlayerId), With<PlayerInputBinding>>,
+) {
+    if !party.is_changed() {
+        return;
+    }
+    for (entity, player_id) in binding_query.iter() {
+        if !party.player_map.contains_uuid(&player_id.0) {
+            commands.entity(entity).remove::<PlayerInputBinding>();
+        }
+    }
+}
+
+// Added by the plugin when a gamepad join button is set
+pub fn join_party_on_gamepad_press(
+    mut input: PlayerInputHandler,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let PlayerInputHandler { handler, input_binding_query } = &mut input;
+    let join_button = match handler.plugin.get_gamepad_join_button() {
+        Ok(Some(join_button)) => *join_button,
+        _ => return,
+    };
+
+    // One join per frame, on_player_component_spawned seats a single new player each update
+    let gamepad = gamepad_buttons
+        .get_just_pressed()
+        .filter(|button| button.button_type == join_button)
+        .map(|button| button.gamepad)
+        .find(|gamepad| handler.party.get_player_by_input_binding(input_binding_query, &PlayerInputBinding::Gamepad(*gamepad)).is_none());
+    let Some(gamepad) = gamepad else {
+        return;
+    };
+    if handler.phi.verify_if_party_size_exceeds_limit(&handler.plugin, &mut handler.party, &handler.player_query).is_err() {
+        return;
+    }
+
+    let player_uuid = Uuid::now_v7();
+    let username = format!("PlayerLocal Gamepad {}", gamepad.id);
+    if let Err(e) = handler.phi.pipeline_db_and_party_add_new_synced_player_local_with_uuid(&mut handler.commands, &mut handler.party, &handler.player_query, &handler.plugin, &username, player_uuid) {
+        warn!("join_party_on_gamepad_press -> pipeline_db_and_party_add_new_synced_player_local [{:?}] Error: {:?}", gamepad, e);
+        return;
+    }
+    // Queued behind the spawn, so the PlayerIndex already knows the new entity
+    handler.commands.add(move |world: &mut World| {
+        let entity = world.resource::<PlayerIndex>().get_entity(&player_uuid);
+        if let Some(entity) = entity {
+            world.entity_mut(entity).insert(PlayerInputBinding::Gamepad(gamepad));
+        }
+    });
+}
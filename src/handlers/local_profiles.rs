@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use uuid::Uuid;
+
+use crate::{
+    DBPlayer,
+    PartyEvent,
+    PlayerHandlerError,
+    PlayerProfileHandler,
+    PlayerRecordInserted,
+    PlayerType,
+    ProfileSelection,
+};
+
+impl<'w, 's> PlayerProfileHandler<'w, 's> {
+    pub fn list_local_profiles(
+        &self,
+    ) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        let mut profiles = self.handler.storage.list_players()?;
+        profiles.retain(|player| player.get_player_type() == &PlayerType::PlayerMain);
+        Ok(profiles)
+    }
+
+    // Stores a new PlayerMain record without selecting it, returns its uuid
+    pub fn create_profile(
+        &mut self,
+        email: &str,
+        username: &str,
+    ) -> Result<Uuid, ErrorTypePlayerHandler> {
+        let profile_uuid = Uuid::now_v7();
+        self.handler.storage.insert_player(&DBPlayer::new(&profile_uuid, Some(&email.to_owned()), Some(&username.to_owned()), PlayerType::PlayerMain))?;
+        self.handler.party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+            player_uuid: profile_uuid,
+            player_type: PlayerType::PlayerMain,
+        }));
+        Ok(profile_uuid)
+    }
+
+    // Marks the profile as the main player, spawns it and lets the rest of startup run
+    pub fn select_profile(
+        &mut self,
+        profile_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if *self.profile_selection.get() == ProfileSelection::Selected || matches!(*self.next_profile_selection, NextState::Pending(_)) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("select_profile failed: a profile is already selected".to_string()))
+        }
+        self.verify_local_profile(profile_uuid)?;
+
+        self.handler.storage.set_main_player(profile_uuid)?;
+        self.handler.phi.pipeline_db_and_party_add_main_player_from_db_to_party(&mut self.handler.commands, &self.handler.storage, profile_uuid, &self.handler.plugin)?;
+        self.handler.plugin.set_main_player_uuid(profile_uuid)?;
+        self.handler.party.set_main_player_uuid(profile_uuid)?;
+        self.next_profile_selection.set(ProfileSelection::Selected);
+        Ok(())
+    }
+
+    // The selected profile can not be deleted, pipeline_db_and_party_action_remove_player refuses the main player
+    pub fn delete_profile(
+        &mut self,
+        profile_uuid: &Uuid,
+    ) -> Result<(), PlayerHandlerError> {
+        self.verify_local_profile(profile_uuid)?;
+        self.handler.remove(profile_uuid)
+    }
+
+    fn verify_local_profile(
+        &self,
+        profile_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let profile_uuid_string = profile_uuid.to_string();
+        let profiles = self.list_local_profiles()?;
+        if !profiles.iter().any(|profile| profile.get_uuid_string() == &profile_uuid_string) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("verify_local_profile failed: [{}] is not a local profile", profile_uuid)))
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,18 @@
 pub mod database;
+pub mod delta;
+pub mod input_binding;
+pub mod local_profiles;
 pub mod party;
-pub mod player;
\ No newline at end of file
+pub mod party_slots;
+pub mod player;
+pub mod requests;
+pub mod session;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+pub mod writer;
+pub mod player_handler;
+pub mod player_index;
+pub mod preset;
+pub mod profile;
+pub mod remote;
\ No newline at end of file
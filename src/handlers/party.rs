@@ -7,45 +7,90 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
+    ActivePlayerChanged,
     BevyEasyPlayerHandlerPlugin, 
+    MainPlayerChanged,
     Party, 
+    PartyChange,
+    PartyDelta,
+    PartyEvent,
+    PartyReordered,
+    PartySeatingMode,
+    PartySlots,
+    PlayerBundle,
     PlayerComponent,
+    PlayerData,
+    PlayerDataItem,
+    PlayerEmail,
+    PlayerId,
+    PlayerLeftParty,
+    PlayerIndex,
+    PlayerName,
     PlayerType,
 };
 
-macro_rules! player_query_get_player_lock {
-    ($player_query:expr, $target_uuid:expr) => {{
-        let mut player_match: Option<&PlayerComponent> = None;
-        for player in $player_query.iter() {
-            let player_lock = match player.player.lock() {
-                Ok(player) => player,
-                Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e))),
-            };
-            let player_id = player_lock.get_player_id().unwrap();
-            if $target_uuid == Some(*player_id) {
-                player_match = Some(player);
-            }
-        }
-        player_match
-    }};
+// Finds the party member carrying the target uuid through the PlayerIndex instead of scanning the query
+fn player_query_get_player<'a>(
+    player_index: &PlayerIndex,
+    player_query: &'a Query<PlayerData>,
+    target_uuid: Option<Uuid>,
+) -> Option<PlayerDataItem<'a>> {
+    let entity = player_index.get_entity(&target_uuid?)?;
+    player_query.get(entity).ok()
 }
 
 impl Party {
     pub fn new() -> Self {
         let active_player: usize = 1;
         let main_player_uuid: Option<Uuid> = None;
-        let player_map: HashMap<usize, Uuid> = HashMap::new();
+        let player_map: PartySlots = PartySlots::new();
+        let pending_events: Vec<PartyEvent> = Vec::new();
+        let revision: u64 = 0;
+        let delta_log: Vec<PartyDelta> = Vec::new();
         Party {
             active_player,
             main_player_uuid,
             player_map,
+            pending_events,
+            revision,
+            delta_log,
         } 
     }
 
+    pub fn queue_event(
+        &mut self,
+        event: PartyEvent,
+    ) {
+        // Seat and active changes are logged as deltas too, joins carry more than the event so on_player_component_spawned records those
+        let change = match &event {
+            PartyEvent::PlayerLeftParty(left) => Some(PartyChange::Left {
+                player_uuid: left.player_uuid,
+                seat: left.party_index,
+            }),
+            PartyEvent::ActivePlayerChanged(active) => Some(PartyChange::ActiveChanged {
+                active_player: active.active_index,
+            }),
+            PartyEvent::PartyReordered(reordered) => Some(PartyChange::Reordered {
+                player_map: reordered.player_map.clone(),
+            }),
+            _ => None,
+        };
+        if let Some(change) = change {
+            self.record_delta(change);
+        }
+        self.pending_events.push(event);
+    }
+
+    pub fn drain_pending_events(
+        &mut self,
+    ) -> Vec<PartyEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
     pub fn get_player_map_clone(
         &self
     ) -> Result<HashMap<usize, Uuid>, ErrorTypePlayerHandler> {
-        let result = self.player_map.clone();
+        let result = self.player_map.to_map();
         Ok(result)
     }
 
@@ -53,31 +98,29 @@ impl Party {
         &self
     ) -> Result<Option<Uuid>, ErrorTypePlayerHandler> {
         let active_player = self.active_player;
-        let result = self.player_map.get(&active_player); 
-        Ok(Some(*result.unwrap()))
+        let result = self.player_map.get(&active_player).copied(); 
+        Ok(result)
     }
 
     pub fn clone_player(
         &self,
         target_uuid: &Uuid,
-        player_query: &Query<&PlayerComponent>,
-    ) -> Result<PlayerComponent, ErrorTypePlayerHandler> {
-        let mut player_match: Vec<&PlayerComponent> = 
-            player_query
-                .iter().filter(
-                    |player|{
-                        let player_mutex = player.to_owned().player.lock().unwrap();
-                        let player_id = player_mutex.get_player_id().unwrap().clone();
-                        drop(player_mutex);
-                        return target_uuid == &player_id;
-                    }
-                )
-                .collect();
-            
-        let player = player_match.remove(0);
-        drop(player_match);
-        let player_component: PlayerComponent = PlayerComponent{ player: player.player.clone() };
-        Ok(player_component)
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
+    ) -> Result<PlayerBundle, ErrorTypePlayerHandler> {
+        let player = player_query_get_player(player_index, player_query, Some(*target_uuid));
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_player -> player [{}] is not in the party", target_uuid)))
+        }
+        let player = player.unwrap();
+        let player_bundle = PlayerBundle {
+            marker: PlayerComponent,
+            id: *player.id,
+            kind: player.kind.clone(),
+            name: player.name.clone(),
+            email: player.email.clone(),
+        };
+        Ok(player_bundle)
     }
 
     pub fn get_active_player_index(&self) -> Result<usize, ErrorTypePlayerHandler> {
@@ -89,140 +132,127 @@ impl Party {
         &mut self, 
         target: usize, 
     ) -> Result<(), ErrorTypePlayerHandler> {
+        let previous_index = self.active_player;
         self.active_player = target;
+        if previous_index != target {
+            let player_uuid = self.player_map.get(&target).copied();
+            self.queue_event(PartyEvent::ActivePlayerChanged(ActivePlayerChanged {
+                previous_index,
+                active_index: target,
+                player_uuid,
+            }));
+        }
         Ok(())
     }
 
     pub fn clone_active_player_player_type(
         &self,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
     ) -> Result<PlayerType, ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_player_type -> player.is_none()".to_string()))
         }
-        let player_component = player_component.unwrap();
-        let player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            }
-        };
-        let player_type = player_mutex.get_player_type()?.clone();
-        drop(player_mutex);
-        Ok(player_type.to_owned())
+        let player_type = player.unwrap().kind.0.clone();
+        Ok(player_type)
     }
 
     pub fn clone_active_player_player_email(
         &self,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
     ) -> Result<String, ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_player_email -> player.is_none()".to_string()))
+        }
+        match &player.unwrap().email.0 {
+            Some(player_email) => Ok(player_email.to_owned()),
+            None => Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_player_email Error: Missing Player Email".to_string())),
         }
-        let player_component = player_component.unwrap();
-        let player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            },
-        };
-        let player_email = player_mutex.get_player_email()?.clone();
-        drop(player_mutex);
-        Ok(player_email.to_owned())
     }
 
     pub fn clone_active_player_uuid(
         &self,
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
     ) -> Result<Uuid, ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_uuid -> player.is_none()".to_string()))
         }
-        let player_component = player_component.unwrap();
-        let player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            }        };
-        let player_id = player_mutex.get_player_id()?.clone();
-        drop(player_mutex);
+        let player_id = player.unwrap().id.0;
         Ok(player_id)
     }
 
     pub fn clone_active_player_player_username(
         &self, 
-        player_query: &Query<&PlayerComponent>,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>,
     ) -> Result<String, ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_player_username -> player.is_none()".to_string()))
+        }
+        match &player.unwrap().name.0 {
+            Some(player_username) => Ok(player_username.to_owned()),
+            None => Err(ErrorTypePlayerHandler::PartyActionFailed("clone_active_player_player_username Error: Missing Player User Name".to_string())),
         }
-        let player_component = player_component.unwrap();
-        let player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            }
-        };
-        let player_username = player_mutex.get_player_username()?.to_owned();
-        drop(player_mutex);
-        Ok(player_username.to_owned())
     }
 
     pub fn set_active_player_email(
         &mut self, 
-        player_query: &Query<&PlayerComponent>, 
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>, 
         player_email: &str,
     ) -> Result<(), ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_email -> player.is_none()".to_string()))
         }
-        let player_component = player_component.unwrap();
-        let mut player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            }
-        };
-        player_mutex.set_player_email(player_email)?;
-        drop(player_mutex);
+        let player = player.unwrap();
+        if player.email.0.as_deref() == Some(player_email) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_email Error: New Email matches existing email".to_string()))
+        }
+        commands.entity(player.entity).insert(PlayerEmail(Some(player_email.to_owned())));
         Ok(())
     }
 
     pub fn set_active_player_username(
         &mut self, 
-        player_query: &Query<&PlayerComponent>, 
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>, 
         player_username: &str,
     ) -> Result<(), ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
-        }
-        let player_component = player_component.unwrap();
-        let mut player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => {
-                return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e)));
-            }
-        };
-        player_mutex.set_player_username(player_username)?;
-        drop(player_mutex);
+        let player = player_query_get_player(player_index, player_query, target_uuid);
+        if player.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_username -> player.is_none()".to_string()))
+        }
+        let player = player.unwrap();
+        if player.name.0.as_deref() == Some(player_username) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_username Error: New username matches existing username".to_string()))
+        }
+        commands.entity(player.entity).insert(PlayerName(Some(player_username.to_owned())));
+        self.record_delta(PartyChange::Renamed {
+            player_uuid: player.id.0,
+            username: Some(player_username.to_owned()),
+        });
         Ok(())
     }
 
     pub fn set_active_player_uuid_player_map_and_component(
         &mut self, 
-        player_query: &Query<&PlayerComponent>, 
+        commands: &mut Commands,
+        player_index: &PlayerIndex, 
         new_uuid: Uuid
     ) -> Result<(), ErrorTypePlayerHandler> {
         let active_player_uuid_option = self.get_player_map_active_player_uuid()?;
@@ -235,7 +265,7 @@ impl Party {
         if active_player_uuid == new_uuid {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("active_player_set_uuid failed: new id matches existing")));
         }
-        self.set_active_player_uuid_player_component(player_query, &new_uuid)?;
+        self.set_active_player_uuid_player_component(commands, player_index, &new_uuid)?;
         self.set_active_player_uuid_player_map(&new_uuid)?;
     
         let updated_active_player_uuid = self.get_player_map_active_player_uuid()?;
@@ -247,19 +277,20 @@ impl Party {
 
     pub fn init_main_player_uuid_player_map(
         &mut self, 
-        player_query: &Query<&PlayerComponent>, 
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        player_query: &Query<PlayerData>, 
         new_uuid: Uuid
     ) -> Result<(), ErrorTypePlayerHandler> {
-        let active_player = match player_query.single().player.lock() {
-            Ok(uuid) => uuid,
-            Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e))),
+        let active_player = match player_query.get_single() {
+            Ok(player) => player,
+            Err(e) => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("init_main_player_uuid_player_map failed: [{}]", e))),
         };
-        let active_player_uuid = active_player.get_player_id()?.clone();
-        drop(active_player);
+        let active_player_uuid = active_player.id.0;
         if &active_player_uuid == &new_uuid {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("init_main_player_uuid_player_map failed: new id matches existing")));
         }
-        self.set_active_player_uuid_player_component(player_query, &new_uuid)?;
+        self.set_active_player_uuid_player_component(commands, player_index, &new_uuid)?;
         self.set_active_player_uuid_player_map(&new_uuid)?;
         let updated_active_player_uuid = self.get_player_map_active_player_uuid()?;
         if Some(active_player_uuid) == updated_active_player_uuid {
@@ -270,21 +301,19 @@ impl Party {
 
     pub fn set_active_player_uuid_player_component(
         &mut self, 
-        player_query: &Query<&PlayerComponent>, 
+        commands: &mut Commands,
+        player_index: &PlayerIndex, 
         new_uuid: &Uuid,
     ) -> Result<(), ErrorTypePlayerHandler> {
         let target_uuid = self.get_player_map_active_player_uuid()?;
-        let player_component = player_query_get_player_lock!(player_query, target_uuid);
-        if player_component.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("clone_active_player_player_type -> player_component.is_none()")))
+        let entity = target_uuid.and_then(|target_uuid| player_index.get_entity(&target_uuid));
+        if entity.is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_uuid_player_component -> entity.is_none()".to_string()))
         }
-        let player_component = player_component.unwrap();
-        let mut player_mutex = match player_component.player.lock(){
-            Ok(player) => player,
-            Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e))),
-        };
-        player_mutex.set_player_id(*new_uuid)?;
-        drop(player_mutex);
+        if target_uuid.as_ref() == Some(new_uuid) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("set_active_player_uuid_player_component Error: New id matches existing id".to_string()))
+        }
+        commands.entity(entity.unwrap()).insert(PlayerId(*new_uuid));
         Ok(())
     }
 
@@ -293,29 +322,20 @@ impl Party {
         new_uuid: &Uuid,
     ) -> Result<(), ErrorTypePlayerHandler> {
         let target_index = self.active_player;
-        let player_map = &mut self.player_map;
-        player_map.entry(target_index).and_modify(
-            |uuid| 
-            {*uuid = *new_uuid}
-        );
+        if self.player_map.get(&target_index).is_some() {
+            self.player_map.insert(target_index, *new_uuid, target_index)?;
+        }
         Ok(())
     }
 
     pub fn clone_main_player_uuid(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<Uuid, ErrorTypePlayerHandler> {
         let mut return_id: Option<Uuid> = None;
         for player in player_query.iter() {
-            let player_container = &player.player;
-            let player_lock = match player_container.lock() {
-                Ok(uuid) => uuid,
-                Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("{}", e))),
-            };
-            let player_type = player_lock.get_player_type()?;
-            if player_type == &PlayerType::PlayerMain {
-                let player_uuid = player_lock.get_player_id()?;
-                return_id = Some(*player_uuid);
+            if player.kind.0 == PlayerType::PlayerMain {
+                return_id = Some(player.id.0);
             }
         }
         if return_id.is_none() {
@@ -327,36 +347,29 @@ impl Party {
 
     pub fn get_all_players_ids(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<Vec<Uuid>, ErrorTypePlayerHandler> {
         let mut id_storage: Vec<Uuid> = Vec::new();
         for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_id = player_mutex.get_player_id()?.clone();
-            drop(player_mutex);
-            id_storage.push(player_id);
+            id_storage.push(player.id.0);
         }
         Ok(id_storage)
     }
 
     pub fn get_all_players_ids_and_types(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<Vec<(Uuid, PlayerType)>, ErrorTypePlayerHandler> {
         let mut id_type_storage: Vec<(Uuid, PlayerType)> = Vec::new();
         for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_id = player_mutex.get_player_id()?.clone();
-            let player_type = player_mutex.get_player_type()?.clone();
-            drop(player_mutex);
-            id_type_storage.push((player_id, player_type));
+            id_type_storage.push((player.id.0, player.kind.0.clone()));
         }
         Ok(id_type_storage)
     }
 
     pub fn get_player_count_party(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
         let count_ai_all = self.get_player_count_ai_total(&player_query)?;
         let count_local = self.get_player_count_local(&player_query)?;
@@ -367,24 +380,18 @@ impl Party {
 
     pub fn get_player_count_main(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
-        let mut count: usize = 0;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerMain => count += 1,
-                _ => {},
-            };
-            drop(player_mutex);
-        };
+        let count = player_query
+            .iter()
+            .filter(|player| player.kind.0 == PlayerType::PlayerMain)
+            .count();
         Ok(count)
     }
 
     pub fn get_player_count_ai_total(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
         let count_ai = self.get_player_count_ai_local(&player_query)?;
         let count_remote = self.get_player_count_ai_remote(&player_query)?;
@@ -393,85 +400,58 @@ impl Party {
 
     pub fn get_player_count_ai_local(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
-        let mut count: usize = 0;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerAiLocal => count += 1,
-                _ => {},
-            };
-            drop(player_mutex);
-        };
+        let count = player_query
+            .iter()
+            .filter(|player| player.kind.0 == PlayerType::PlayerAiLocal)
+            .count();
         Ok(count)
     }
 
     pub fn get_player_count_ai_remote(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
-        let mut count: usize = 0;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerAiRemote => count += 1,
-                _ => {},
-            };
-            drop(player_mutex);
-        };
+        let count = player_query
+            .iter()
+            .filter(|player| player.kind.0 == PlayerType::PlayerAiRemote)
+            .count();
         Ok(count)
     }
 
     pub fn get_player_count_local(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
-        let mut count: usize = 0;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerLocal => count += 1,
-                _ => {},
-            };
-            drop(player_mutex);
-        };
+        let count = player_query
+            .iter()
+            .filter(|player| player.kind.0 == PlayerType::PlayerLocal)
+            .count();
         Ok(count)
     }
 
     pub fn get_player_count_remote(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<usize, ErrorTypePlayerHandler> {
-        let mut count: usize = 0;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerRemote => count += 1,
-                _ => {},
-            };
-            drop(player_mutex);
-        };
+        let count = player_query
+            .iter()
+            .filter(|player| player.kind.0 == PlayerType::PlayerRemote)
+            .count();
         Ok(count)
     }
 
     pub fn verify_player_exists_player_map_and_component(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_index: &PlayerIndex, 
         target_id: &Uuid
     ) -> Result<(bool, bool), ErrorTypePlayerHandler> {
         let exists_player_map = match self.verify_player_exists_player_map_uuid(target_id) {
             Ok(bool) => bool,
             Err(e) => return Err(e),
         };
-        let exists_player_component = match self.verify_player_exists_player_component(player_query, target_id) {
-            Ok(bool) => bool,
-            Err(e) => return Err(e),
-        };
+        let exists_player_component = self.verify_player_exists_player_component(player_index, target_id)?;
         Ok((exists_player_map, exists_player_component))
     }
 
@@ -479,13 +459,7 @@ impl Party {
         &self, 
         target_id: &Uuid,
     ) -> Result<bool, ErrorTypePlayerHandler> {
-        let mut exists = false;
-        let player_map = &self.player_map;
-        for player in player_map {
-            if target_id == player.1 {
-                exists = true;
-            }
-        }        
+        let exists = self.player_map.contains_uuid(target_id);
         Ok(exists)
     }
 
@@ -493,50 +467,28 @@ impl Party {
         &self, 
         target_idx: usize,
     ) -> Result<bool, ErrorTypePlayerHandler> {
-        let mut exists = false;
-        let player_map = &self.player_map;
-        for player in player_map {
-            if &target_idx == player.0 {
-                exists = true;
-                return Ok(exists)
-            }
-        }
-        return Ok(exists)
+        let exists = self.player_map.get(&target_idx).is_some();
+        Ok(exists)
     }
 
     pub fn verify_player_exists_player_component(
         &self, 
-        player_query: &Query<&PlayerComponent>, 
+        player_index: &PlayerIndex, 
         target_id: &Uuid
     ) -> Result<bool, ErrorTypePlayerHandler> {
-        let mut exists = false;
-        for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_id = player_mutex.get_player_id()?;
-            if target_id == player_id {
-                exists = true;
-            }
-            drop(player_mutex);
-        }
+        let exists = player_index.contains(target_id);
         Ok(exists)
     }
 
     pub fn get_party_local_ai_uuids_vec(
         &self,
-        player_query: &Query<&PlayerComponent>, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<Vec<Uuid>, ErrorTypePlayerHandler> {
         let mut ai_index: Vec<Uuid> = Vec::new();
         for player in player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_type = player_mutex.get_player_type()?;
-            match player_type {
-                &PlayerType::PlayerAiLocal => {
-                    let player_id = player_mutex.get_player_id()?.to_owned();
-                    ai_index.push(player_id);
-                },
-                _ => {},
-            };
-            drop(player_mutex);
+            if player.kind.0 == PlayerType::PlayerAiLocal {
+                ai_index.push(player.id.0);
+            }
         }
         Ok(ai_index)
     }
@@ -544,8 +496,8 @@ impl Party {
     pub fn remove_player_ai(
         &self,
         commands: &mut Commands,
-        player_query: &Query<&PlayerComponent>, 
-        entity_player_query: &Query<(Entity, &PlayerComponent)>, 
+        player_index: &PlayerIndex, 
+        player_query: &Query<PlayerData>, 
     ) -> Result<(), ErrorTypePlayerHandler> {
         // Check for Ai Uuid
         let ai_vec = self.get_party_local_ai_uuids_vec(player_query)?;
@@ -555,14 +507,9 @@ impl Party {
         let target = ai_vec[0];
 
         // Find the first ai player
-        for (entity, player) in entity_player_query.iter() {
-            let player_mutex = player.player.lock().unwrap();
-            let player_id = player_mutex.get_player_id()?;
-            if &target == player_id {
-                  commands.entity(entity).despawn_recursive();
-                  return Ok(());
-            };
-            drop(player_mutex);
+        if let Some(entity) = player_index.get_entity(&target) {
+            commands.entity(entity).despawn_recursive();
+            return Ok(());
         }
         return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("remove_player_ai: Failed... Target does not exist...")));
     }
@@ -570,7 +517,7 @@ impl Party {
     pub fn remove_player(
         &mut self,
         commands: &mut Commands,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>,
+        player_index: &PlayerIndex,
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
         target_player: &Uuid,
     ) -> Result<(), ErrorTypePlayerHandler> {
@@ -578,17 +525,8 @@ impl Party {
         if main_player == target_player {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("Unable to remove player... Main Player is local host...")))
         }
-        let mut despawned = false;
-        for (entity, player) in entity_player_query {
-            let player_mutex = player.player.lock().unwrap();
-            let player_id = player_mutex.get_player_id()?;
-            if player_id == target_player {
-                despawned = true;
-                commands.entity(entity).despawn_recursive();
-            }
-            drop(player_mutex);
-        }
-        if despawned {
+        if let Some(entity) = player_index.get_entity(target_player) {
+            commands.entity(entity).despawn_recursive();
             self.player_map_remove_player(plugin, target_player)?;
             return Ok(());
         } else {
@@ -601,24 +539,27 @@ impl Party {
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
         target_player: &Uuid,
     ) -> Result<(), ErrorTypePlayerHandler> {
-        // Step 1: Find the key to remove using an immutable borrow
-        let target = self
-            .player_map
-            .iter()
-            .find(|(_, player_uuid)| *player_uuid == target_player)
-            .map(|(key, _)| *key);
-
+        let target = self.player_map.seat_of(target_player);
         if target.is_none() {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(
                 "remove_player_from_player_map failed... Target does not exist...".to_string(),
             ));
         }
 
-        // Step 2: Remove the key using a mutable borrow
+        // Open the seat, the collapse below closes it unless seats are fixed
         let target = target.unwrap();
-        self.player_map.remove(&target);
-
+        self.player_map.take(&target);
+        self.queue_event(PartyEvent::PlayerLeftParty(PlayerLeftParty {
+            player_uuid: *target_player,
+            party_index: target,
+        }));
+
+        // Compact seating shifts everyone behind the gap forward, so an active seat at or behind the gap steps back with them.
+        // The collapse goes first so the ActiveChanged delta names a seat that is taken by then
         self.player_map_check_for_players_and_collapse_missing(plugin)?;
+        if *plugin.get_party_seating_mode()? == PartySeatingMode::Compact && target <= self.active_player {
+            self.set_active_player_index(self.active_player.saturating_sub(1).max(1))?;
+        }
 
         Ok(())
     }
@@ -634,7 +575,14 @@ impl Party {
         &mut self,
         target_uuid: &Uuid,
     ) -> Result<Uuid, ErrorTypePlayerHandler> {
+        let previous_uuid = self.main_player_uuid;
         self.main_player_uuid = Some(*target_uuid);
+        if previous_uuid != self.main_player_uuid {
+            self.queue_event(PartyEvent::MainPlayerChanged(MainPlayerChanged {
+                previous_uuid,
+                main_player_uuid: *target_uuid,
+            }));
+        }
         let result = self.main_player_uuid;
         if result.is_none() {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("set_main_player_uuid failed... New Uuid did not set properly")))
@@ -645,23 +593,17 @@ impl Party {
     pub fn player_map_and_component_remove_all_players_besides_main(
         &mut self,
         commands: &mut Commands,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>, 
+        entity_player_query: &Query<(Entity, &PlayerId)>, 
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
     ) -> Result<(), ErrorTypePlayerHandler> {
         self.set_active_player_index(1)?;
         let main_player_id = self.get_main_player_uuid()?.unwrap();
         // let main_player_id = main_player_id.expect("main_player_id unwrap failed");
-        for (entity, player) in entity_player_query.iter() {
-            let player_mutex = match player.player.lock() {
-                Ok(mutex) => mutex,
-                Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("player_map_and_component_remove_all_players failed: [{:?}]", e)))
-            };
-            let player_id = player_mutex.get_player_id()?;
-            if player_id != &main_player_id {
+        for (entity, player_id) in entity_player_query.iter() {
+            if player_id.0 != main_player_id {
                 commands.entity(entity).despawn_recursive();    
-                self.player_map_remove_player(plugin, player_id)?;            
+                self.player_map_remove_player(plugin, &player_id.0)?;            
             }
-            drop(player_mutex);
         }
         Ok(())
     }
@@ -669,19 +611,13 @@ impl Party {
     pub fn player_map_and_component_remove_all_players(
         &mut self,
         commands: &mut Commands,
-        entity_player_query: &Query<(Entity, &PlayerComponent)>, 
+        entity_player_query: &Query<(Entity, &PlayerId)>, 
         plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
     ) -> Result<(), ErrorTypePlayerHandler> {
         self.set_active_player_index(1)?;
-        for (entity, player) in entity_player_query.iter() {
-            let player_mutex = match player.player.lock() {
-                Ok(mutex) => mutex,
-                Err(e) => return Err(ErrorTypePlayerHandler::PoisonErrorBox(format!("player_map_and_component_remove_all_players failed: [{:?}]", e)))
-            };
-            let player_id = player_mutex.get_player_id()?;
-            self.player_map_remove_player(plugin, player_id)?;
+        for (entity, player_id) in entity_player_query.iter() {
+            self.player_map_remove_player(plugin, &player_id.0)?;
             commands.entity(entity).despawn_recursive();                
-            drop(player_mutex);
         }
         Ok(())
     }
@@ -694,65 +630,43 @@ impl Party {
         if party_limit.is_none() {
             return Err(ErrorTypePlayerHandler::PluginDataRetreivalFailed(format!("plugin.get_party_size_limit()? is None...")))
         }
-        let party_limit: usize = *party_limit.unwrap();
-        let mut party_idx: usize = 0;
+        // Fixed seats leave the gap open for the next player to fill
+        if *plugin.get_party_seating_mode()? == PartySeatingMode::FixedSeats {
+            return Ok(());
+        }
+        if self.player_map.compact() {
+            let player_map = self.player_map.clone();
+            self.queue_event(PartyEvent::PartyReordered(PartyReordered { player_map }));
+        }
+        Ok(())
+    }
 
-        // Build the reorder reference vec
-        let mut reorder_list: Vec<(usize, bool)> = Vec::new(); 
-        loop {
-            party_idx += 1;
-            reorder_list.push((party_idx, false));
-            if party_idx == party_limit {
-                break;
-            }
-            else {
-                continue;
-            }
+    pub fn player_map_insert_player_at(
+        &mut self,
+        target_index: usize,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if self.player_map.contains_uuid(player_uuid) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("player_map_insert_player_at failed: [{}] is already in the party", player_uuid)))
         }
+        self.player_map.insert_at(target_index, *player_uuid)?;
+        let player_map = self.player_map.clone();
+        self.queue_event(PartyEvent::PartyReordered(PartyReordered { player_map }));
+        Ok(())
+    }
 
-        // iterate through the reorder reference vec and identify player_map entries that need to be shifted
-        loop {
-            // Update the reorder reference vec with real values
-            for n in 0..party_limit {
-                if self.verify_player_exists_player_map_index(n + 1)? { //indexed logic
-                    reorder_list[n].1 = true;
-                } else {
-                    reorder_list[n].1 = false;
-                }
-            }
-            // create containers for first false and next existing
-            let mut first_false: Option<usize> = None;
-            let mut next_existing: Option<usize> = None;
-            
-            // Identify first false and next existing
-            for entry in reorder_list.iter() {
-                match entry.1 {
-                    false => {
-                        first_false = Some(entry.0);
-                    },
-                    true => {
-                        if first_false.is_some() {
-                            next_existing = Some(entry.0);
-                            break;
-                        }
-                    },
-                }
-            }
-            if first_false.is_none() {
-                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("player_map_check_for_players_and_collapse_missing failed: [ Party full, no index gap to collapse ]")))
-            };
-            if next_existing.is_none() {
-                break;
-            };
-            let target = next_existing.unwrap();
-            let target_uuid = self.player_map.get(&target);
-            if target_uuid.is_none() {
-                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("player_map_check_for_players_and_collapse_missing -> extracted_uuid.is_none()")))
-            };
-            let extracted_uuid = target_uuid.unwrap();
-            self.player_map.insert(first_false.unwrap(), *extracted_uuid);
-            self.player_map.remove(&next_existing.unwrap());
+    pub fn player_map_move_player(
+        &mut self,
+        old_index: usize,
+        new_index: usize,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if old_index == new_index {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed("player_map_move_player failed... new == old, No change...".to_string()))
         }
+        self.player_map.move_seat(old_index, new_index)?;
+        let player_map = self.player_map.clone();
+        self.queue_event(PartyEvent::PartyReordered(PartyReordered { player_map }));
+        self.set_active_player_index(new_index)?;
         Ok(())
     }
 
@@ -764,30 +678,10 @@ impl Party {
         if old_index == new_index {
             return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("reorder_players failed... new == old, No change...")))
         }
-        let old_uuid = self
-            .player_map
-            .iter()
-            .find(|(index, _)| *index == &old_index)
-            .map(|(_, uuid)| *uuid);
-        if old_uuid.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("old_uuid.is_none()")));
-        };
-        let old_uuid = old_uuid.unwrap();
-        
-        let new_uuid = self
-            .player_map
-            .iter()
-            .find(|(index, _)| *index == &new_index)
-            .map(|(_, uuid)| *uuid);
-        if new_uuid.is_none() {
-            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("old_uuid.is_none()")));
-        };
-        let new_uuid = new_uuid.unwrap();
-        
-        self.set_active_player_index(old_index)?;
-        self.set_active_player_uuid_player_map(&new_uuid)?;
+        self.player_map.swap(old_index, new_index)?;
+        let player_map = self.player_map.clone();
+        self.queue_event(PartyEvent::PartyReordered(PartyReordered { player_map }));
         self.set_active_player_index(new_index)?;
-        self.set_active_player_uuid_player_map(&old_uuid)?;
 
         Ok(())
     }
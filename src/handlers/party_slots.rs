@@ -0,0 +1,220 @@
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::PartySlots;
+
+impl PartySlots {
+    pub fn new() -> Self {
+        PartySlots {
+            slots: Vec::new(),
+        }
+    }
+
+    // Occupied seats, matching the old HashMap len
+    pub fn len(
+        &self,
+    ) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(
+        &self,
+    ) -> bool {
+        self.len() == 0
+    }
+
+    // Highest seat in use, open seats before it included
+    pub fn seat_count(
+        &self,
+    ) -> usize {
+        self.slots.len()
+    }
+
+    pub fn get(
+        &self,
+        seat: &usize,
+    ) -> Option<&Uuid> {
+        let slot = seat.checked_sub(1)?;
+        self.slots.get(slot)?.as_ref()
+    }
+
+    pub fn seat_of(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.as_ref() == Some(player_uuid))
+            .map(|slot| slot + 1)
+    }
+
+    pub fn contains_uuid(
+        &self,
+        player_uuid: &Uuid,
+    ) -> bool {
+        self.seat_of(player_uuid).is_some()
+    }
+
+    // The active seat has to be taken, apart from seat 1 where an emptied party rests
+    pub fn can_be_active(
+        &self,
+        seat: &usize,
+    ) -> bool {
+        *seat == 1 || self.get(seat).is_some()
+    }
+
+    pub fn first_open_seat(
+        &self,
+    ) -> usize {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_none())
+            .unwrap_or(self.slots.len()) + 1
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (usize, &Uuid)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, player_uuid)| player_uuid.as_ref().map(|player_uuid| (slot + 1, player_uuid)))
+    }
+
+    // Places the player in the seat, opening any seats before it, and returns whoever sat there. The seat must be in 1..=seat_limit and the player can not already hold another seat
+    pub fn insert(
+        &mut self,
+        seat: usize,
+        player_uuid: Uuid,
+        seat_limit: usize,
+    ) -> Result<Option<Uuid>, ErrorTypePlayerHandler> {
+        if seat == 0 || seat > seat_limit {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("insert failed: seat [{}] is outside 1..={}", seat, seat_limit)))
+        }
+        if let Some(held_seat) = self.seat_of(&player_uuid) {
+            if held_seat != seat {
+                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("insert failed: [{}] already holds seat [{}]", player_uuid, held_seat)))
+            }
+        }
+        let slot = seat - 1;
+        if slot >= self.slots.len() {
+            self.slots.resize(slot + 1, None);
+        }
+        Ok(self.slots[slot].replace(player_uuid))
+    }
+
+    // Seats the player at the seat and shifts everyone from there back by one
+    pub fn insert_at(
+        &mut self,
+        seat: usize,
+        player_uuid: Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if seat == 0 || seat > self.slots.len() + 1 {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("insert_at failed: seat [{}] is outside 1..={}", seat, self.slots.len() + 1)))
+        }
+        self.slots.insert(seat - 1, Some(player_uuid));
+        Ok(())
+    }
+
+    pub fn push(
+        &mut self,
+        player_uuid: Uuid,
+    ) -> usize {
+        self.slots.push(Some(player_uuid));
+        self.slots.len()
+    }
+
+    // Removes the player and shifts everyone behind them forward
+    pub fn remove(
+        &mut self,
+        seat: &usize,
+    ) -> Option<Uuid> {
+        let slot = seat.checked_sub(1)?;
+        if slot >= self.slots.len() {
+            return None;
+        }
+        let player_uuid = self.slots.remove(slot);
+        self.trim_open_seats();
+        player_uuid
+    }
+
+    // Removes the player and leaves the seat open
+    pub fn take(
+        &mut self,
+        seat: &usize,
+    ) -> Option<Uuid> {
+        let slot = seat.checked_sub(1)?;
+        let player_uuid = self.slots.get_mut(slot)?.take();
+        self.trim_open_seats();
+        player_uuid
+    }
+
+    pub fn move_seat(
+        &mut self,
+        from: usize,
+        to: usize,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if self.get(&from).is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("move_seat failed: seat [{}] is empty", from)))
+        }
+        if to == 0 || to > self.slots.len() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("move_seat failed: seat [{}] is outside 1..={}", to, self.slots.len())))
+        }
+        let player_uuid = self.slots.remove(from - 1);
+        self.slots.insert(to - 1, player_uuid);
+        Ok(())
+    }
+
+    pub fn swap(
+        &mut self,
+        seat_a: usize,
+        seat_b: usize,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if self.get(&seat_a).is_none() || self.get(&seat_b).is_none() {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("swap failed: seats [{}] and [{}] must both be taken", seat_a, seat_b)))
+        }
+        self.slots.swap(seat_a - 1, seat_b - 1);
+        Ok(())
+    }
+
+    // Closes every open seat keeping the seating order, returns whether anyone moved
+    pub fn compact(
+        &mut self,
+    ) -> bool {
+        let seat_count = self.slots.len();
+        self.slots.retain(|slot| slot.is_some());
+        self.slots.len() != seat_count
+    }
+
+    pub fn clear(
+        &mut self,
+    ) {
+        self.slots.clear();
+    }
+
+    pub fn to_map(
+        &self,
+    ) -> HashMap<usize, Uuid> {
+        self.iter().map(|(seat, player_uuid)| (seat, *player_uuid)).collect()
+    }
+
+    fn trim_open_seats(
+        &mut self,
+    ) {
+        while let Some(None) = self.slots.last() {
+            self.slots.pop();
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a PartySlots {
+    type Item = (usize, &'a Uuid);
+    type IntoIter = Box<dyn Iterator<Item = (usize, &'a Uuid)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
@@ -2,8 +2,14 @@ use crate::{
     Player,
     PlayerAiLocal,
     PlayerAiRemote,
+    PlayerBundle,
+    PlayerComponent,
+    PlayerEmail,
+    PlayerId,
+    PlayerKind,
     PlayerLocal,
     PlayerMain,
+    PlayerName,
     PlayerRemote,
     PlayerType,
 };
@@ -481,3 +487,84 @@ impl Player for PlayerRemote {
         ));
     }
 }
+
+// --------------------------------------- //
+
+// The bundle is what gets spawned, implementing Player on it keeps the trait usable as a facade over the components
+impl PlayerBundle {
+    pub fn from_player(player: &dyn Player) -> Result<Self, ErrorTypePlayerHandler> {
+        Ok(PlayerBundle::new(
+            player.get_player_email().ok().cloned(),
+            player.get_player_username().ok().cloned(),
+            Some(*player.get_player_id()?),
+            player.get_player_type()?.clone(),
+        ))
+    }
+
+    fn call_failed(&self, message: String) -> ErrorTypePlayerHandler {
+        match self.kind.0 {
+            PlayerType::PlayerAiLocal | PlayerType::PlayerAiRemote => ErrorTypePlayerHandler::PlayerAiCallFailed(message),
+            PlayerType::PlayerLocal => ErrorTypePlayerHandler::PlayerLocalCallFailed(message),
+            PlayerType::PlayerMain | PlayerType::PlayerTestRef => ErrorTypePlayerHandler::PlayerMainCallFailed(message),
+            PlayerType::PlayerRemote => ErrorTypePlayerHandler::PlayerRemoteCallFailed(message),
+        }
+    }
+}
+
+impl Player for PlayerBundle {
+    fn new(player_email: Option<String>, player_username: Option<String>, player_uuid: Option<Uuid>, player_type: PlayerType) -> Self {
+        PlayerBundle {
+            marker: PlayerComponent,
+            id: PlayerId(player_uuid.unwrap_or_else(Uuid::now_v7)),
+            kind: PlayerKind(player_type),
+            name: PlayerName(player_username),
+            email: PlayerEmail(player_email),
+        }
+    }
+
+    fn get_player_email(&self) -> Result<&String, ErrorTypePlayerHandler> {
+        match &self.email.0 {
+            Some(player_email) => Ok(player_email),
+            None => Err(self.call_failed("PlayerBundle::get_player_email() Error: Missing Player Email".to_string())),
+        }
+    }
+
+    fn get_player_id(&self) -> Result<&Uuid, ErrorTypePlayerHandler> {
+        Ok(&self.id.0)
+    }
+
+    fn get_player_type(&self) -> Result<&PlayerType, ErrorTypePlayerHandler> {
+        Ok(&self.kind.0)
+    }
+
+    fn get_player_username(&self) -> Result<&String, ErrorTypePlayerHandler> {
+        match &self.name.0 {
+            Some(player_username) => Ok(player_username),
+            None => Err(self.call_failed("PlayerBundle::get_player_username() Error: Missing Player User Name".to_string())),
+        }
+    }
+
+    fn set_player_email(&mut self, new_email: &str) -> Result<(), ErrorTypePlayerHandler> {
+        if self.email.0.as_deref() == Some(new_email) {
+            return Err(self.call_failed("PlayerBundle::set_player_email() Error: New Email matches existing email".to_string()));
+        }
+        self.email.0 = Some(new_email.to_owned());
+        Ok(())
+    }
+
+    fn set_player_id(&mut self, new_id: Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        if self.id.0 == new_id {
+            return Err(self.call_failed("PlayerBundle::set_player_id() Error: New id matches existing id".to_string()));
+        }
+        self.id.0 = new_id;
+        Ok(())
+    }
+
+    fn set_player_username(&mut self, new_username: &str) -> Result<(), ErrorTypePlayerHandler> {
+        if self.name.0.as_deref() == Some(new_username) {
+            return Err(self.call_failed("PlayerBundle::set_player_username() Error: New username matches existing username".to_string()));
+        }
+        self.name.0 = Some(new_username.to_owned());
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,67 @@
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use uuid::Uuid;
+
+use crate::{
+    PlayerHandler,
+    PlayerHandlerError,
+};
+
+impl<'w, 's> PlayerHandler<'w, 's> {
+    pub fn add_local(
+        &mut self,
+        username: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.phi.pipeline_db_and_party_add_new_synced_player_local(&mut self.commands, &mut self.party, &self.player_query, &self.plugin, username)
+    }
+
+    pub fn add_ai(
+        &mut self,
+        username: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.phi.pipeline_db_and_party_add_new_synced_player_ai_local(&mut self.commands, &mut self.party, &self.player_query, &self.plugin, username)
+    }
+
+    pub fn load_from_db(
+        &mut self,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.db_writer.flush()?;
+        self.phi.pipeline_db_and_party_add_player_from_db_to_party(&mut self.commands, &self.storage, player_uuid, &mut self.party, &self.player_query, &mut self.plugin)
+    }
+
+    // Fails with PlayerHandlerError::PlayerNotFound when no record matches player_uuid
+    pub fn remove(
+        &mut self,
+        player_uuid: &Uuid,
+    ) -> Result<(), PlayerHandlerError> {
+        self.db_writer.flush()?;
+        self.phi.pipeline_db_and_party_action_remove_player(&mut self.commands, &self.storage, &mut self.party, &self.player_index, player_uuid, &mut self.plugin)
+    }
+
+    // Queued writes land before the wipe, so none of them can bring a record back afterwards
+    pub fn reset(
+        &mut self,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.db_writer.flush()?;
+        self.phi.pipeline_db_and_party_remove_all_build_test_ref_and_init_new_main_player(&self.storage, &mut self.commands, &self.entity_player_query, &mut self.party, &mut self.plugin)
+    }
+
+    pub fn active(
+        &self,
+    ) -> Result<Option<Uuid>, ErrorTypePlayerHandler> {
+        let active_player = self.party.get_active_player_index()?;
+        Ok(self.party.player_map.get(&active_player).copied())
+    }
+
+    pub fn set_active(
+        &mut self,
+        player_uuid: &Uuid,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let target_index = self.party.player_map.seat_of(player_uuid);
+        match target_index {
+            Some(index) => self.party.set_active_player_index(index),
+            None => Err(ErrorTypePlayerHandler::PartyActionFailed(format!("set_active failed: player [{}] is not in the party", player_uuid))),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+
+use uuid::Uuid;
+
+use crate::PlayerIndex;
+
+impl PlayerIndex {
+    pub fn contains(
+        &self,
+        player_uuid: &Uuid,
+    ) -> bool {
+        self.entities.contains_key(player_uuid)
+    }
+
+    pub fn get_entity(
+        &self,
+        player_uuid: &Uuid,
+    ) -> Option<Entity> {
+        self.entities.get(player_uuid).copied()
+    }
+
+    pub fn get_uuid(
+        &self,
+        entity: Entity,
+    ) -> Option<Uuid> {
+        self.uuids.get(&entity).copied()
+    }
+
+    pub fn insert(
+        &mut self,
+        entity: Entity,
+        player_uuid: Uuid,
+    ) {
+        // A re-inserted PlayerId replaces the entity's previous uuid
+        if let Some(previous_uuid) = self.uuids.insert(entity, player_uuid) {
+            if previous_uuid != player_uuid && self.entities.get(&previous_uuid) == Some(&entity) {
+                self.entities.remove(&previous_uuid);
+            }
+        }
+        self.entities.insert(player_uuid, entity);
+    }
+
+    pub fn is_empty(
+        &self,
+    ) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn len(
+        &self,
+    ) -> usize {
+        self.entities.len()
+    }
+
+    pub fn remove_entity(
+        &mut self,
+        entity: Entity,
+    ) -> Option<Uuid> {
+        let player_uuid = self.uuids.remove(&entity)?;
+        if self.entities.get(&player_uuid) == Some(&entity) {
+            self.entities.remove(&player_uuid);
+        }
+        Some(player_uuid)
+    }
+}
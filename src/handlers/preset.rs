@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use uuid::Uuid;
+
+use crate::{
+    DBPartyPreset,
+    PartyPresetLoad,
+    PlayerPresetHandler,
+};
+
+impl<'w, 's> PlayerPresetHandler<'w, 's> {
+    pub fn save_current_party_as_preset(
+        &mut self,
+        name: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let members: Vec<(usize, Uuid)> = self.handler.party.player_map
+            .iter()
+            .map(|(seat, player_uuid)| (seat, *player_uuid))
+            .collect();
+        self.handler.storage.save_party_preset(name, &members)
+    }
+
+    pub fn list_presets(
+        &self,
+    ) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.handler.storage.party_presets()
+    }
+
+    pub fn delete_preset(
+        &mut self,
+        name: &str,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.handler.storage.delete_party_preset(name)
+    }
+
+    // Queues every member who is not seated yet, they join the next frame through pipeline_db_and_party_add_player_from_db_to_party and take the next free seats.
+    // Members whose player record is gone are reported as missing, nothing is queued when the rest would not fit in the party.
+    pub fn load_preset(
+        &mut self,
+        name: &str,
+    ) -> Result<PartyPresetLoad, ErrorTypePlayerHandler> {
+        self.handler.db_writer.flush()?;
+        let preset = match self.handler.storage.party_preset(name)? {
+            Some(preset) => preset,
+            None => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("load_preset failed: no preset named [{}]", name))),
+        };
+
+        let mut report = PartyPresetLoad {
+            name: preset.name.clone(),
+            ..Default::default()
+        };
+        for (_, player_uuid) in preset.members.iter() {
+            if self.handler.party.player_map.contains_uuid(player_uuid) || self.preset_load_queue.pending.contains(player_uuid) {
+                report.already_seated.push(*player_uuid);
+            } else if !self.handler.storage.player_exists(player_uuid)? {
+                warn!("load_preset [{}] -> player [{}] no longer exists", name, player_uuid);
+                report.missing.push(*player_uuid);
+            } else {
+                report.queued.push(*player_uuid);
+            }
+        }
+
+        if let Some(party_size) = self.handler.plugin.get_party_size_limit()? {
+            if self.handler.party.player_map.len() + self.preset_load_queue.pending.len() + report.queued.len() > *party_size {
+                return Err(ErrorTypePlayerHandler::PartySizeGreaterThanSetLimit)
+            }
+        }
+        self.preset_load_queue.pending.extend(report.queued.iter().copied());
+        Ok(report)
+    }
+}
@@ -0,0 +1,255 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use rusqlite::types::{
+    ToSql,
+    ToSqlOutput,
+    Value,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    PlayerId,
+    PlayerProfile,
+    PlayerProfileHandler,
+    PlayerStorage,
+    ProfileValue,
+};
+
+// The codes below are persisted in player_profile.value_type, never renumber an existing entry.
+impl ProfileValue {
+    pub fn to_db_code(&self) -> i32 {
+        match self {
+            ProfileValue::Bool(_) => 1,
+            ProfileValue::Integer(_) => 2,
+            ProfileValue::Real(_) => 3,
+            ProfileValue::Text(_) => 4,
+        }
+    }
+
+    pub fn from_db(value_type: i32, value: Value) -> Result<ProfileValue, ErrorTypePlayerHandler> {
+        match (value_type, value) {
+            (1, Value::Integer(value)) => Ok(ProfileValue::Bool(value != 0)),
+            (2, Value::Integer(value)) => Ok(ProfileValue::Integer(value)),
+            (3, Value::Real(value)) => Ok(ProfileValue::Real(value)),
+            (3, Value::Integer(value)) => Ok(ProfileValue::Real(value as f64)),
+            (4, Value::Text(value)) => Ok(ProfileValue::Text(value)),
+            (value_type, value) => Err(ErrorTypePlayerHandler::DBQueryMappingFailed(format!("ProfileValue::from_db failed: value [{:?}] does not match value_type [{}]", value, value_type))),
+        }
+    }
+}
+
+impl ToSql for ProfileValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match self {
+            ProfileValue::Bool(value) => Ok(ToSqlOutput::from(*value)),
+            ProfileValue::Integer(value) => Ok(ToSqlOutput::from(*value)),
+            ProfileValue::Real(value) => Ok(ToSqlOutput::from(*value)),
+            ProfileValue::Text(value) => Ok(ToSqlOutput::from(value.as_str())),
+        }
+    }
+}
+
+impl From<bool> for ProfileValue {
+    fn from(value: bool) -> Self {
+        ProfileValue::Bool(value)
+    }
+}
+
+impl From<i32> for ProfileValue {
+    fn from(value: i32) -> Self {
+        ProfileValue::Integer(value as i64)
+    }
+}
+
+impl From<i64> for ProfileValue {
+    fn from(value: i64) -> Self {
+        ProfileValue::Integer(value)
+    }
+}
+
+impl From<u32> for ProfileValue {
+    fn from(value: u32) -> Self {
+        ProfileValue::Integer(value as i64)
+    }
+}
+
+impl From<f32> for ProfileValue {
+    fn from(value: f32) -> Self {
+        ProfileValue::Real(value as f64)
+    }
+}
+
+impl From<f64> for ProfileValue {
+    fn from(value: f64) -> Self {
+        ProfileValue::Real(value)
+    }
+}
+
+impl From<&str> for ProfileValue {
+    fn from(value: &str) -> Self {
+        ProfileValue::Text(value.to_owned())
+    }
+}
+
+impl From<String> for ProfileValue {
+    fn from(value: String) -> Self {
+        ProfileValue::Text(value)
+    }
+}
+
+// A mismatched read hands the stored value back as the error
+impl TryFrom<ProfileValue> for bool {
+    type Error = ProfileValue;
+
+    fn try_from(value: ProfileValue) -> Result<Self, Self::Error> {
+        match value {
+            ProfileValue::Bool(value) => Ok(value),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<ProfileValue> for i64 {
+    type Error = ProfileValue;
+
+    fn try_from(value: ProfileValue) -> Result<Self, Self::Error> {
+        match value {
+            ProfileValue::Integer(value) => Ok(value),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<ProfileValue> for f64 {
+    type Error = ProfileValue;
+
+    fn try_from(value: ProfileValue) -> Result<Self, Self::Error> {
+        match value {
+            ProfileValue::Real(value) => Ok(value),
+            value => Err(value),
+        }
+    }
+}
+
+impl TryFrom<ProfileValue> for String {
+    type Error = ProfileValue;
+
+    fn try_from(value: ProfileValue) -> Result<Self, Self::Error> {
+        match value {
+            ProfileValue::Text(value) => Ok(value),
+            value => Err(value),
+        }
+    }
+}
+
+impl PlayerProfile {
+    pub fn new() -> Self {
+        PlayerProfile::default()
+    }
+
+    pub fn len(
+        &self,
+    ) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(
+        &self,
+    ) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get_value(
+        &self,
+        key: &str,
+    ) -> Option<&ProfileValue> {
+        self.values.get(key)
+    }
+
+    // None when the key is missing or holds another type
+    pub fn get<T: TryFrom<ProfileValue>>(
+        &self,
+        key: &str,
+    ) -> Option<T> {
+        T::try_from(self.values.get(key)?.clone()).ok()
+    }
+
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: impl Into<ProfileValue>,
+    ) -> Option<ProfileValue> {
+        self.values.insert(key.to_owned(), value.into())
+    }
+
+    pub fn remove(
+        &mut self,
+        key: &str,
+    ) -> Option<ProfileValue> {
+        self.values.remove(key)
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (&String, &ProfileValue)> + '_ {
+        self.values.iter()
+    }
+}
+
+impl<'w, 's> PlayerProfileHandler<'w, 's> {
+    // Writes the stored profile and keeps the PlayerProfile of a spawned player in step
+    pub fn set_profile_value(
+        &mut self,
+        player_uuid: &Uuid,
+        key: &str,
+        value: impl Into<ProfileValue>,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let value: ProfileValue = value.into();
+        // The profile row needs the player record, which may still be queued on the writer
+        self.handler.db_writer.flush()?;
+        self.handler.storage.set_profile_value(player_uuid, key, value.clone())?;
+        if let Some(entity) = self.handler.player_index.get_entity(player_uuid) {
+            let key = key.to_owned();
+            self.handler.commands.add(move |world: &mut World| {
+                if let Some(mut profile) = world.get_mut::<PlayerProfile>(entity) {
+                    profile.set(&key, value);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    pub fn profile_value<T: TryFrom<ProfileValue, Error = ProfileValue>>(
+        &self,
+        player_uuid: &Uuid,
+        key: &str,
+    ) -> Result<Option<T>, ErrorTypePlayerHandler> {
+        match self.handler.storage.player_profile(player_uuid)?.get_value(key) {
+            Some(value) => T::try_from(value.clone())
+                .map(Some)
+                .map_err(|value| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("profile_value [{}] failed: stored value is [{:?}]", key, value))),
+            None => Ok(None),
+        }
+    }
+}
+
+// Loads the stored profile onto players as they spawn, or when their uuid changes
+pub fn load_player_profiles(
+    mut commands: Commands,
+    storage: Res<PlayerStorage>,
+    changed_query: Query<(Entity, &PlayerId), Changed<PlayerId>>,
+) {
+    for (entity, player_id) in changed_query.iter() {
+        let profile = match storage.player_profile(&player_id.0) {
+            Ok(profile) => profile,
+            Err(e) => {
+                warn!("load_player_profiles -> storage.player_profile [{}] Error: {:?}", player_id.0, e);
+                PlayerProfile::default()
+            },
+        };
+        commands.entity(entity).try_insert(profile);
+    }
+}
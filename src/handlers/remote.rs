@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{
+    LoopbackTransport,
+    Player,
+    PlayerBundle,
+    PlayerHandler,
+    PlayerHandlerError,
+    PlayerId,
+    PlayerType,
+    RemoteConnectionState,
+    RemotePeerEvent,
+    RemotePlayerTransport,
+    RemoteTransport,
+};
+
+impl RemotePlayerTransport {
+    pub fn new(transport: impl RemoteTransport) -> Self {
+        RemotePlayerTransport {
+            transport: Box::new(transport),
+        }
+    }
+
+    pub fn poll_events(&mut self) -> Vec<RemotePeerEvent> {
+        self.transport.poll_events()
+    }
+}
+
+impl LoopbackTransport {
+    pub fn new() -> Self {
+        LoopbackTransport::default()
+    }
+
+    pub fn send(&self, event: RemotePeerEvent) {
+        match self.queue.lock() {
+            Ok(mut queue) => queue.push_back(event),
+            Err(_) => warn!("LoopbackTransport::send -> queue lock poisoned, event dropped"),
+        }
+    }
+}
+
+impl RemoteTransport for LoopbackTransport {
+    fn poll_events(&mut self) -> Vec<RemotePeerEvent> {
+        match self.queue.lock() {
+            Ok(mut queue) => queue.drain(..).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+// Applies transport events to the party. A joining peer is spawned at most once per frame, so the events behind it see the new player in the PlayerIndex
+pub fn poll_remote_transport(
+    mut handler: PlayerHandler,
+    transport: Option<ResMut<RemotePlayerTransport>>,
+    mut pending: Local<VecDeque<RemotePeerEvent>>,
+    mut state_query: Query<&mut RemoteConnectionState>,
+) {
+    if let Some(mut transport) = transport {
+        pending.extend(transport.poll_events());
+    }
+
+    while let Some(event) = pending.pop_front() {
+        let (player_uuid, new_state) = match &event {
+            RemotePeerEvent::Connecting { player_uuid, username, player_type } => {
+                if !handler.player_index.contains(player_uuid) {
+                    if *player_type != PlayerType::PlayerRemote && *player_type != PlayerType::PlayerAiRemote {
+                        warn!("poll_remote_transport -> [{}] joined as [{:?}], only remote player types can join through a transport", player_uuid, player_type);
+                        continue;
+                    }
+                    if let Err(e) = handler.phi.verify_if_party_size_exceeds_limit(&handler.plugin, &mut handler.party, &handler.player_query) {
+                        warn!("poll_remote_transport -> [{}] can not join: {:?}", player_uuid, e);
+                        continue;
+                    }
+                    handler.commands.spawn((
+                        PlayerBundle::new(None, username.clone(), Some(*player_uuid), player_type.clone()),
+                        RemoteConnectionState::Connecting,
+                    ));
+                    break;
+                }
+                (player_uuid, RemoteConnectionState::Connecting)
+            },
+            RemotePeerEvent::Connected { player_uuid } => (player_uuid, RemoteConnectionState::Connected),
+            RemotePeerEvent::Disconnected { player_uuid } => (player_uuid, RemoteConnectionState::Disconnected { elapsed: Duration::ZERO }),
+            RemotePeerEvent::Left { player_uuid } => (player_uuid, RemoteConnectionState::Dropped),
+        };
+        let state = handler.player_index
+            .get_entity(player_uuid)
+            .and_then(|entity| state_query.get_mut(entity).ok());
+        match state {
+            Some(mut state) => {
+                // A repeated disconnect keeps the grace period that is already running
+                let already_disconnected = matches!(*state, RemoteConnectionState::Disconnected { .. });
+                if !(already_disconnected && matches!(new_state, RemoteConnectionState::Disconnected { .. })) {
+                    *state = new_state;
+                }
+            },
+            None => warn!("poll_remote_transport -> [{:?}] does not match a remote party member", event),
+        }
+    }
+}
+
+// Runs the grace period of disconnected remotes and removes those that timed out or left.
+// The join stored a player record, so they leave through PlayerHandler::remove and take it with them
+pub fn update_remote_connections(
+    mut handler: PlayerHandler,
+    time: Res<Time>,
+    mut state_query: Query<(Entity, &PlayerId, &mut RemoteConnectionState)>,
+) {
+    let grace_period = match handler.plugin.get_remote_grace_period() {
+        Ok(grace_period) => *grace_period,
+        Err(_) => Duration::ZERO,
+    };
+    for (entity, player_id, mut state) in state_query.iter_mut() {
+        if let RemoteConnectionState::Disconnected { elapsed } = *state {
+            let elapsed = elapsed + time.delta();
+            *state = match elapsed >= grace_period {
+                true => RemoteConnectionState::TimedOut,
+                false => RemoteConnectionState::Disconnected { elapsed },
+            };
+        }
+        if !matches!(*state, RemoteConnectionState::TimedOut | RemoteConnectionState::Dropped) {
+            continue;
+        }
+        match handler.remove(&player_id.0) {
+            Ok(()) => (),
+            // A record that never landed leaves nothing behind, only the seat and entity go
+            Err(PlayerHandlerError::PlayerNotFound(_)) => {
+                let PlayerHandler { commands, party, player_index, plugin, .. } = &mut handler;
+                if let Err(e) = party.remove_player(commands, player_index, plugin, &player_id.0) {
+                    warn!("update_remote_connections -> party.remove_player [{}] Error: {:?}", player_id.0, e);
+                    commands.entity(entity).despawn_recursive();
+                }
+            },
+            Err(e) => {
+                warn!("update_remote_connections -> handler.remove [{}] Error: {:?}", player_id.0, e);
+                handler.commands.entity(entity).despawn_recursive();
+            },
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::{
+    ecs::system::SystemState,
+    prelude::*,
+};
+
+use std::collections::VecDeque;
+
+use crate::{
+    LoadPlayerFromDbRequest,
+    PlayerHandler,
+    PlayerHandlerError,
+    PlayerRequest,
+    PlayerRequestReaders,
+    PlayerRequestResult,
+    PresetLoadQueue,
+};
+
+// System to run the pipelines behind the request events
+// Every pending request is handled in the frame it arrives, the handler's commands are applied after each one so the next sees the entities it spawned or despawned
+pub fn handle_player_requests(
+    world: &mut World,
+    readers: &mut SystemState<PlayerRequestReaders>,
+    handler_state: &mut SystemState<PlayerHandler>,
+) {
+    let mut pending = VecDeque::new();
+    let mut requests = readers.get_mut(world);
+    for request in requests.add_local.read() {
+        pending.push_back(PlayerRequest::AddLocalPlayer(request.clone()));
+    }
+    for request in requests.add_ai.read() {
+        pending.push_back(PlayerRequest::AddAiPlayer(request.clone()));
+    }
+    for request in requests.remove.read() {
+        pending.push_back(PlayerRequest::RemovePlayer(request.clone()));
+    }
+    for request in requests.load.read() {
+        pending.push_back(PlayerRequest::LoadPlayerFromDb(request.clone()));
+    }
+    for request in requests.reset.read() {
+        pending.push_back(PlayerRequest::ResetRoster(request.clone()));
+    }
+    for uuid in world.resource_mut::<PresetLoadQueue>().pending.drain(..) {
+        pending.push_back(PlayerRequest::LoadPlayerFromDb(LoadPlayerFromDbRequest { uuid }));
+    }
+
+    while let Some(request) = pending.pop_front() {
+        let mut handler = handler_state.get_mut(world);
+        let result = match &request {
+            PlayerRequest::AddLocalPlayer(request) => handler.add_local(&request.username).map_err(PlayerHandlerError::from),
+            PlayerRequest::AddAiPlayer(request) => handler.add_ai(&request.username).map_err(PlayerHandlerError::from),
+            PlayerRequest::RemovePlayer(request) => handler.remove(&request.uuid),
+            PlayerRequest::LoadPlayerFromDb(request) => handler.load_from_db(&request.uuid).map_err(PlayerHandlerError::from),
+            PlayerRequest::ResetRoster(_) => handler.reset().map_err(PlayerHandlerError::from),
+        };
+        handler_state.apply(world);
+        if let Err(e) = &result {
+            warn!("handle_player_requests -> [{:?}] Failed: [{:?}]", &request, e);
+        }
+        world.send_event(PlayerRequestResult {
+            request,
+            result,
+        });
+    }
+}
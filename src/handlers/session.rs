@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    Party,
+    PlayerJoinedParty,
+    PlayerSession,
+    PlayerStorage,
+};
+
+// Unix seconds, the unit every timestamp column uses
+pub fn unix_timestamp_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn begin_player_session(
+    storage: Res<PlayerStorage>,
+    party: Res<Party>,
+    mut session: ResMut<PlayerSession>,
+) {
+    if session.session_id.is_some() {
+        return;
+    }
+    let players: Vec<(usize, Uuid)> = party.player_map
+        .iter()
+        .map(|(seat, player_uuid)| (seat, *player_uuid))
+        .collect();
+    match storage.begin_session(&players) {
+        Ok(session_id) => session.session_id = Some(session_id),
+        Err(e) => warn!("begin_player_session -> storage.begin_session Error: {:?}", e),
+    }
+}
+
+// Adds players who join during the session and closes it on AppExit
+pub fn track_player_session(
+    storage: Res<PlayerStorage>,
+    party: Res<Party>,
+    mut session: ResMut<PlayerSession>,
+    mut player_joined: EventReader<PlayerJoinedParty>,
+    mut app_exit: EventReader<AppExit>,
+) {
+    let Some(session_id) = session.session_id else {
+        player_joined.clear();
+        app_exit.clear();
+        return;
+    };
+    for joined in player_joined.read() {
+        if let Err(e) = storage.add_session_player(session_id, joined.party_index, &joined.player_uuid) {
+            warn!("track_player_session -> storage.add_session_player [{}] Error: {:?}", joined.player_uuid, e);
+        }
+    }
+    if app_exit.read().count() == 0 {
+        return;
+    }
+    let players: Vec<Uuid> = party.player_map
+        .iter()
+        .map(|(_, player_uuid)| *player_uuid)
+        .collect();
+    match storage.end_session(session_id, &players) {
+        Ok(()) => session.session_id = None,
+        Err(e) => warn!("track_player_session -> storage.end_session Error: {:?}", e),
+    }
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::{
+    ActivePlayerChanged,
+    BevyEasyPlayerHandlerPlugin,
+    Party,
+    PartyEvent,
+    PartyReordered,
+    PartySlots,
+    PartySnapshot,
+    PartySnapshotPlayer,
+    Player,
+    PlayerBundle,
+    PlayerData,
+    PlayerIndex,
+    PlayerKind,
+    PlayerName,
+};
+
+impl Party {
+    pub fn snapshot(
+        &self,
+        player_query: &Query<PlayerData>,
+    ) -> Result<PartySnapshot, ErrorTypePlayerHandler> {
+        let players_by_uuid: HashMap<Uuid, _> = player_query
+            .iter()
+            .map(|player| (player.id.0, player))
+            .collect();
+        let mut players: Vec<PartySnapshotPlayer> = Vec::new();
+        for (seat, player_uuid) in self.player_map.iter() {
+            let player = match players_by_uuid.get(player_uuid) {
+                Some(player) => player,
+                None => return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("snapshot failed: seat [{}] player [{}] has no entity", seat, player_uuid))),
+            };
+            players.push(PartySnapshotPlayer {
+                seat,
+                player_uuid: *player_uuid,
+                player_type: player.kind.0.clone(),
+                username: player.name.0.clone(),
+            });
+        }
+        Ok(PartySnapshot {
+            revision: self.revision,
+            active_player: self.active_player,
+            main_player_uuid: self.main_player_uuid,
+            players,
+        })
+    }
+
+    // Rebuilds the roster from a snapshot. Players already spawned keep their entity, the rest are spawned, and party members missing from the snapshot are despawned
+    pub fn apply_snapshot(
+        &mut self,
+        commands: &mut Commands,
+        player_index: &PlayerIndex,
+        plugin: &mut ResMut<BevyEasyPlayerHandlerPlugin>,
+        snapshot: &PartySnapshot,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        // Every seat is checked against the party size before it is stored, a snapshot from the network decides how far the slots grow
+        let party_size = plugin.get_party_size_limit()?.copied().unwrap_or(1);
+        if snapshot.players.len() > party_size {
+            return Err(ErrorTypePlayerHandler::PartySizeGreaterThanSetLimit)
+        }
+        let mut player_map = PartySlots::new();
+        for player in snapshot.players.iter() {
+            if player_map.contains_uuid(&player.player_uuid) {
+                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_snapshot failed: player [{}] is listed twice", player.player_uuid)))
+            }
+            if player.seat == 0 || player_map.get(&player.seat).is_some() {
+                return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_snapshot failed: seat [{}] is 0 or listed twice", player.seat)))
+            }
+            player_map.insert(player.seat, player.player_uuid, party_size)?;
+        }
+        if !player_map.can_be_active(&snapshot.active_player) {
+            return Err(ErrorTypePlayerHandler::PartyActionFailed(format!("apply_snapshot failed: active seat [{}] is not a seated seat", snapshot.active_player)))
+        }
+
+        let snapshot_uuids: HashSet<Uuid> = snapshot.players.iter().map(|player| player.player_uuid).collect();
+        for (_, player_uuid) in self.player_map.iter() {
+            if snapshot_uuids.contains(player_uuid) {
+                continue;
+            }
+            if let Some(entity) = player_index.get_entity(player_uuid) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        for player in snapshot.players.iter() {
+            match player_index.get_entity(&player.player_uuid) {
+                Some(entity) => {
+                    commands.entity(entity).insert((
+                        PlayerKind(player.player_type.clone()),
+                        PlayerName(player.username.clone()),
+                    ));
+                },
+                None => {
+                    commands.spawn(PlayerBundle::new(None, player.username.clone(), Some(player.player_uuid), player.player_type.clone()));
+                },
+            }
+        }
+
+        self.player_map = player_map;
+        if let Some(main_player_uuid) = snapshot.main_player_uuid {
+            self.set_main_player_uuid(&main_player_uuid)?;
+            plugin.set_main_player_uuid(&main_player_uuid)?;
+        }
+        // The snapshot stands in for every delta up to its revision, so these changes are sent as events without being logged again
+        let previous_index = self.active_player;
+        self.active_player = snapshot.active_player;
+        if previous_index != snapshot.active_player {
+            self.pending_events.push(PartyEvent::ActivePlayerChanged(ActivePlayerChanged {
+                previous_index,
+                active_index: snapshot.active_player,
+                player_uuid: self.player_map.get(&snapshot.active_player).copied(),
+            }));
+        }
+        let player_map = self.player_map.clone();
+        self.pending_events.push(PartyEvent::PartyReordered(PartyReordered { player_map }));
+        self.revision = snapshot.revision;
+        self.delta_log.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::{
+    BevyEasyPlayerHandlerPlugin,
+    PlayerDbWriter,
+    PlayerId,
+    PlayerStats,
+    PlayerStorage,
+};
+
+impl PlayerStats {
+    pub fn new() -> Self {
+        PlayerStats::default()
+    }
+
+    // Values as they were read from the database, nothing is marked for writing
+    pub fn from_values(
+        values: impl IntoIterator<Item = (String, i64)>,
+    ) -> Self {
+        PlayerStats {
+            values: values.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn get(
+        &self,
+        stat: &str,
+    ) -> Option<i64> {
+        self.values.get(stat).copied()
+    }
+
+    pub fn set(
+        &mut self,
+        stat: &str,
+        value: i64,
+    ) {
+        self.values.insert(stat.to_owned(), value);
+        self.dirty.insert(stat.to_owned());
+    }
+
+    // Returns the new total, a stat that was never set starts from 0
+    pub fn increment(
+        &mut self,
+        stat: &str,
+        by: i64,
+    ) -> i64 {
+        let value = self.get(stat).unwrap_or(0) + by;
+        self.set(stat, value);
+        value
+    }
+
+    // Keeps the higher of the stored and the new value, returns whether the new one won
+    pub fn record_high(
+        &mut self,
+        stat: &str,
+        value: i64,
+    ) -> bool {
+        if self.get(stat).is_some_and(|high| high >= value) {
+            return false;
+        }
+        self.set(stat, value);
+        true
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (&String, &i64)> + '_ {
+        self.values.iter()
+    }
+
+    pub fn is_dirty(
+        &self,
+    ) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    pub fn dirty_values(
+        &self,
+    ) -> Vec<(String, i64)> {
+        self.dirty
+            .iter()
+            .filter_map(|stat| self.values.get(stat).map(|value| (stat.clone(), *value)))
+            .collect()
+    }
+
+    pub fn clear_dirty(
+        &mut self,
+    ) {
+        self.dirty.clear();
+    }
+}
+
+// Forgets what a player had not written yet, queued ahead of their despawn once their record is deleted so on_player_stats_removed has nothing to bring back
+pub fn discard_unsaved_player_stats(
+    commands: &mut Commands,
+    entity: Entity,
+) {
+    commands.add(move |world: &mut World| {
+        if let Some(mut stats) = world.get_mut::<PlayerStats>(entity) {
+            stats.clear_dirty();
+        }
+    });
+}
+
+// Loads the stored stats onto players as they spawn, or when their uuid changes
+pub fn load_player_stats(
+    mut commands: Commands,
+    storage: Res<PlayerStorage>,
+    changed_query: Query<(Entity, &PlayerId), Changed<PlayerId>>,
+) {
+    for (entity, player_id) in changed_query.iter() {
+        let stats = match storage.player_stats(&player_id.0) {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!("load_player_stats -> storage.player_stats [{}] Error: {:?}", player_id.0, e);
+                PlayerStats::default()
+            },
+        };
+        commands.entity(entity).try_insert(stats);
+    }
+}
+
+// Writes every changed stat in one transaction once the flush interval has passed, or right away when the app is exiting
+pub fn flush_player_stats(
+    storage: Res<PlayerStorage>,
+    db_writer: Res<PlayerDbWriter>,
+    plugin: Res<BevyEasyPlayerHandlerPlugin>,
+    time: Res<Time>,
+    mut app_exit: EventReader<AppExit>,
+    mut since_flush: Local<Duration>,
+    mut stats_query: Query<(&PlayerId, &mut PlayerStats)>,
+) {
+    *since_flush += time.delta();
+    let exiting = app_exit.read().count() > 0;
+    let interval = match plugin.get_stats_flush_interval() {
+        Ok(interval) => *interval,
+        Err(_) => Duration::ZERO,
+    };
+    if !exiting && *since_flush < interval {
+        return;
+    }
+    *since_flush = Duration::ZERO;
+
+    let batch: Vec<(Uuid, String, i64)> = stats_query
+        .iter()
+        .filter(|(_, stats)| stats.is_dirty())
+        .flat_map(|(player_id, stats)| {
+            stats
+                .dirty_values()
+                .into_iter()
+                .map(move |(stat, value)| (player_id.0, stat, value))
+        })
+        .collect();
+    if batch.is_empty() {
+        return;
+    }
+    // Stats are only kept for stored players, so a record still queued on the writer has to land first
+    if let Err(e) = db_writer.flush() {
+        warn!("flush_player_stats -> db_writer.flush Error: {:?}", e);
+        return;
+    }
+    // A failed write keeps everything marked, the next flush tries again
+    if let Err(e) = storage.write_player_stats(&batch) {
+        warn!("flush_player_stats -> storage.write_player_stats Error: {:?}", e);
+        return;
+    }
+    for (_, mut stats) in stats_query.iter_mut() {
+        if stats.is_dirty() {
+            stats.clear_dirty();
+        }
+    }
+}
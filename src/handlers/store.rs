@@ -0,0 +1,672 @@
+use bevy::prelude::*;
+
+use bevy_easy_shared_definitions::{
+    DatabaseConnection,
+    ErrorTypePlayerHandler,
+};
+
+#[cfg(feature = "file_store")]
+use std::fs;
+#[cfg(feature = "file_store")]
+use std::path::PathBuf;
+use std::sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        migrations::{
+            run_player_table_migrations,
+            PlayerTableMigrationContext,
+        },
+        transaction::run_db_tx,
+    },
+    handlers::session::unix_timestamp_now,
+    DBPartyPreset,
+    DBPlayer,
+    DBSession,
+    MemoryPlayerStore,
+    PlayerDbTx,
+    PlayerHandlerError,
+    PlayerProfile,
+    PlayerRecords,
+    PlayerStats,
+    PlayerStorage,
+    PlayerStore,
+    PlayerType,
+    ProfileValue,
+    SqlitePlayerStore,
+};
+
+#[cfg(feature = "file_store")]
+use crate::{
+    FilePlayerStore,
+    PlayerStoreFormat,
+};
+
+impl PlayerStorage {
+    pub fn new(store: impl PlayerStore) -> Self {
+        PlayerStorage {
+            store: Arc::new(Mutex::new(Box::new(store))),
+        }
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Box<dyn PlayerStore>>, ErrorTypePlayerHandler> {
+        match self.store.lock() {
+            Ok(store) => Ok(store),
+            Err(_) => {
+                error!("Player store lock poisoned.");
+                Err(ErrorTypePlayerHandler::DatabaseLockPoisoned)
+            }
+        }
+    }
+
+    pub fn load(&self) -> Result<(), PlayerHandlerError> {
+        self.lock()?.load()
+    }
+
+    pub fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        self.lock()?.list_players()
+    }
+
+    pub fn get_player(&self, player_uuid: &Uuid) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        self.lock()?.get_player(player_uuid)
+    }
+
+    pub fn player_exists(&self, player_uuid: &Uuid) -> Result<bool, ErrorTypePlayerHandler> {
+        self.lock()?.player_exists(player_uuid)
+    }
+
+    pub fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        self.lock()?.main_player()
+    }
+
+    pub fn insert_player(&self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.insert_player(player))
+    }
+
+    pub fn remove_player(&self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.remove_player(player_uuid))
+    }
+
+    pub fn set_main_player(&self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.set_main_player(player_uuid))
+    }
+
+    pub fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        self.lock()?.player_profile(player_uuid)
+    }
+
+    pub fn set_profile_value(&self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.set_profile_value(player_uuid, key, value))
+    }
+
+    pub fn remove_profile_value(&self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.remove_profile_value(player_uuid, key))
+    }
+
+    pub fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        self.lock()?.player_stats(player_uuid)
+    }
+
+    pub fn write_player_stats(&self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.write_player_stats(batch))
+    }
+
+    pub fn begin_session(&self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.begin_session(players))
+    }
+
+    pub fn add_session_player(&self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.add_session_player(session_id, seat, player_uuid))
+    }
+
+    pub fn end_session(&self, session_id: i64, players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.end_session(session_id, players))
+    }
+
+    pub fn save_party_preset(&self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.save_party_preset(name, members))
+    }
+
+    pub fn delete_party_preset(&self, name: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.with_transaction(|records| records.delete_party_preset(name))
+    }
+
+    pub fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.lock()?.party_presets()
+    }
+
+    pub fn party_preset(&self, name: &str) -> Result<Option<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.lock()?.party_preset(name)
+    }
+
+    // Holds the store lock while f runs, Ok keeps every change f made and an Err drops them all. Do not use the PlayerStorage from inside f
+    pub fn with_transaction<T>(
+        &self,
+        f: impl FnOnce(&mut dyn PlayerRecords) -> Result<T, ErrorTypePlayerHandler>,
+    ) -> Result<T, ErrorTypePlayerHandler> {
+        let mut f = Some(f);
+        let mut result = None;
+        self.lock()?.with_transaction(&mut |records| {
+            let f = f
+                .take()
+                .ok_or_else(|| ErrorTypePlayerHandler::DBActionFailed("PlayerStorage::with_transaction failed: the store ran the transaction more than once".to_string()))?;
+            result = Some(f(records)?);
+            Ok(())
+        })?;
+        result.ok_or_else(|| ErrorTypePlayerHandler::DBActionFailed("PlayerStorage::with_transaction failed: the store did not run the transaction".to_string()))
+    }
+}
+
+// Inserts the configured test reference record unless it is already stored, returns its uuid when it was inserted
+pub(crate) fn insert_test_ref_record(
+    records: &mut dyn PlayerRecords,
+    test_ref_info: Option<&(Uuid, String, String)>,
+) -> Result<Option<Uuid>, ErrorTypePlayerHandler> {
+    let Some((test_ref_uuid, test_ref_username, test_ref_email)) = test_ref_info else {
+        return Ok(None);
+    };
+    if records.player_exists(test_ref_uuid)? {
+        return Ok(None);
+    }
+    records.insert_player(&DBPlayer::new(test_ref_uuid, Some(test_ref_email), Some(test_ref_username), PlayerType::PlayerTestRef))?;
+    Ok(Some(*test_ref_uuid))
+}
+
+fn parse_record_uuid(player: &DBPlayer) -> Result<Uuid, ErrorTypePlayerHandler> {
+    Uuid::parse_str(player.get_uuid_string())
+        .map_err(|e| ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))
+}
+
+impl SqlitePlayerStore {
+    pub fn new(db: &DatabaseConnection) -> Self {
+        SqlitePlayerStore {
+            conn: db.get_connection(),
+            migration_context: PlayerTableMigrationContext::default(),
+        }
+    }
+
+    // Lets the migrations backfill the test reference record of a legacy database
+    pub fn test_ref_uuid(mut self, test_ref_uuid: Option<Uuid>) -> Self {
+        self.migration_context.test_ref_uuid = test_ref_uuid;
+        self
+    }
+}
+
+// The records inside one with_db_tx, so SqlitePlayerStore::with_transaction can hand the transaction out as PlayerRecords
+impl PlayerRecords for PlayerDbTx<'_> {
+    fn insert_player(&mut self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler> {
+        let player_uuid = parse_record_uuid(player)?;
+        self.action_insert_player_record(&player_uuid, Some(player.get_email_string()), Some(player.get_username_string()), player.get_player_type().clone())
+    }
+
+    fn remove_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_remove_player_record(player_uuid)
+    }
+
+    fn remove_all_players(&mut self) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_remove_all_player_records()
+    }
+
+    fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        self.query_db_existing_players()
+    }
+
+    fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        match self.query_db_main_player_exists()? {
+            true => Ok(Some(self.query_db_main_player()?)),
+            false => Ok(None),
+        }
+    }
+
+    fn set_main_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_set_main_player_record(player_uuid)
+    }
+
+    fn player_exists(&self, player_uuid: &Uuid) -> Result<bool, ErrorTypePlayerHandler> {
+        self.query_db_player_exists(player_uuid)
+    }
+
+    fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        self.query_db_player_profile(player_uuid)
+    }
+
+    fn set_profile_value(&mut self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_set_player_profile_value(player_uuid, key, value)
+    }
+
+    fn remove_profile_value(&mut self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_remove_player_profile_value(player_uuid, key)
+    }
+
+    fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        self.query_db_player_stats(player_uuid)
+    }
+
+    fn write_player_stats(&mut self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_write_player_stats(batch)
+    }
+
+    fn begin_session(&mut self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler> {
+        self.action_begin_session(players)
+    }
+
+    fn add_session_player(&mut self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_add_session_player(session_id, seat, player_uuid)
+    }
+
+    fn end_session(&mut self, session_id: i64, players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_end_session(session_id, players)
+    }
+
+    fn save_party_preset(&mut self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_save_party_preset(name, members)
+    }
+
+    fn delete_party_preset(&mut self, name: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.action_delete_party_preset(name)
+    }
+
+    fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.query_db_party_presets()
+    }
+}
+
+impl PlayerRecords for SqlitePlayerStore {
+    fn insert_player(&mut self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.insert_player(player))
+    }
+
+    fn remove_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.remove_player(player_uuid))
+    }
+
+    fn remove_all_players(&mut self) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.remove_all_players())
+    }
+
+    fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.list_players())
+    }
+
+    fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.main_player())
+    }
+
+    fn set_main_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.set_main_player(player_uuid))
+    }
+
+    fn player_exists(&self, player_uuid: &Uuid) -> Result<bool, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.player_exists(player_uuid))
+    }
+
+    fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.player_profile(player_uuid))
+    }
+
+    fn set_profile_value(&mut self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.set_profile_value(player_uuid, key, value.clone()))
+    }
+
+    fn remove_profile_value(&mut self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.remove_profile_value(player_uuid, key))
+    }
+
+    fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.player_stats(player_uuid))
+    }
+
+    fn write_player_stats(&mut self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.write_player_stats(batch))
+    }
+
+    fn begin_session(&mut self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.begin_session(players))
+    }
+
+    fn add_session_player(&mut self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.add_session_player(session_id, seat, player_uuid))
+    }
+
+    fn end_session(&mut self, session_id: i64, players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.end_session(session_id, players))
+    }
+
+    fn save_party_preset(&mut self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.save_party_preset(name, members))
+    }
+
+    fn delete_party_preset(&mut self, name: &str) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.delete_party_preset(name))
+    }
+
+    fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| tx.party_presets())
+    }
+}
+
+impl PlayerStore for SqlitePlayerStore {
+    // The schema is brought up to date before anything reads it
+    fn load(&mut self) -> Result<(), PlayerHandlerError> {
+        let version = run_player_table_migrations(&self.conn, &self.migration_context)?;
+        info!("SqlitePlayerStore::load -> player_table schema version [{}]", version);
+        Ok(())
+    }
+
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn PlayerRecords) -> Result<(), ErrorTypePlayerHandler>) -> Result<(), ErrorTypePlayerHandler> {
+        run_db_tx(&self.conn, |tx| f(tx))
+    }
+}
+
+impl MemoryPlayerStore {
+    pub fn new() -> Self {
+        MemoryPlayerStore::default()
+    }
+}
+
+impl PlayerRecords for MemoryPlayerStore {
+    fn insert_player(&mut self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler> {
+        if self.players.iter().any(|stored| stored.get_uuid_string() == player.get_uuid_string()) {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::insert_player failed: [{}] already exists", player.get_uuid_string())));
+        }
+        self.players.push(player.clone());
+        Ok(())
+    }
+
+    fn remove_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        let player_uuid_string = player_uuid.to_string();
+        let Some(position) = self.players.iter().position(|stored| stored.get_uuid_string() == &player_uuid_string) else {
+            return Err(ErrorTypePlayerHandler::DBQueryFailed(format!("MemoryPlayerStore::remove_player failed: no record for [{}]", player_uuid)));
+        };
+        self.players.remove(position);
+        self.profiles.remove(&player_uuid_string);
+        self.stats.remove(&player_uuid_string);
+        if self.main_player_uuid.as_ref() == Some(&player_uuid_string) {
+            self.main_player_uuid = None;
+        }
+        Ok(())
+    }
+
+    fn remove_all_players(&mut self) -> Result<(), ErrorTypePlayerHandler> {
+        self.players.clear();
+        self.profiles.clear();
+        self.stats.clear();
+        self.main_player_uuid = None;
+        Ok(())
+    }
+
+    fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        Ok(self.players.clone())
+    }
+
+    fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        let Some(main_player_uuid) = self.main_player_uuid.as_ref() else {
+            return Ok(None);
+        };
+        Ok(self.players.iter().find(|stored| stored.get_uuid_string() == main_player_uuid).cloned())
+    }
+
+    fn set_main_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        let player_uuid_string = player_uuid.to_string();
+        if !self.players.iter().any(|stored| stored.get_uuid_string() == &player_uuid_string) {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::set_main_player failed: no record for [{}]", player_uuid)));
+        }
+        self.main_player_uuid = Some(player_uuid_string);
+        Ok(())
+    }
+
+    fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        Ok(self.profiles.get(&player_uuid.to_string()).cloned().unwrap_or_default())
+    }
+
+    fn set_profile_value(&mut self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler> {
+        if !self.player_exists(player_uuid)? {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::set_profile_value failed: no player record matches [{}]", player_uuid)));
+        }
+        self.profiles.entry(player_uuid.to_string()).or_default().set(key, value);
+        Ok(())
+    }
+
+    fn remove_profile_value(&mut self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler> {
+        if let Some(profile) = self.profiles.get_mut(&player_uuid.to_string()) {
+            profile.remove(key);
+        }
+        Ok(())
+    }
+
+    fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        let values = self.stats.get(&player_uuid.to_string()).cloned().unwrap_or_default();
+        Ok(PlayerStats::from_values(values))
+    }
+
+    fn write_player_stats(&mut self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler> {
+        for (player_uuid, stat, value) in batch.iter() {
+            if !self.player_exists(player_uuid)? {
+                continue;
+            }
+            self.stats.entry(player_uuid.to_string()).or_default().insert(stat.clone(), *value);
+        }
+        Ok(())
+    }
+
+    fn begin_session(&mut self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler> {
+        let session_id = self.sessions.iter().map(|session| session.session_id).max().unwrap_or(0) + 1;
+        let mut session = DBSession {
+            session_id,
+            started_at: unix_timestamp_now(),
+            ended_at: None,
+            players: Vec::new(),
+        };
+        for (seat, player_uuid) in players.iter() {
+            if !session.players.iter().any(|(_, seated)| seated == player_uuid) {
+                session.players.push((*seat, *player_uuid));
+            }
+        }
+        self.sessions.push(session);
+        Ok(session_id)
+    }
+
+    fn add_session_player(&mut self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        let Some(session) = self.sessions.iter_mut().find(|session| session.session_id == session_id) else {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::add_session_player failed: no session matches [{}]", session_id)));
+        };
+        if !session.players.iter().any(|(_, seated)| seated == player_uuid) {
+            session.players.push((seat, *player_uuid));
+        }
+        Ok(())
+    }
+
+    fn end_session(&mut self, session_id: i64, _players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler> {
+        let Some(session) = self.sessions.iter_mut().find(|session| session.session_id == session_id) else {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::end_session failed: no session matches [{}]", session_id)));
+        };
+        session.ended_at = Some(unix_timestamp_now());
+        Ok(())
+    }
+
+    // Kept sorted by name with the members in seat order, the same order the SQLite store reads them back in
+    fn save_party_preset(&mut self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler> {
+        let mut members = members.to_vec();
+        members.sort_by_key(|(seat, _)| *seat);
+        let preset = DBPartyPreset {
+            name: name.to_owned(),
+            created_at: unix_timestamp_now(),
+            members,
+        };
+        match self.presets.binary_search_by(|stored| stored.name.as_str().cmp(name)) {
+            Ok(position) => self.presets[position] = preset,
+            Err(position) => self.presets.insert(position, preset),
+        }
+        Ok(())
+    }
+
+    fn delete_party_preset(&mut self, name: &str) -> Result<(), ErrorTypePlayerHandler> {
+        let Some(position) = self.presets.iter().position(|stored| stored.name == name) else {
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("MemoryPlayerStore::delete_party_preset failed: no preset named [{}]", name)));
+        };
+        self.presets.remove(position);
+        Ok(())
+    }
+
+    fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        Ok(self.presets.clone())
+    }
+}
+
+impl PlayerStore for MemoryPlayerStore {
+    // Works on a copy, which only replaces the records once f has succeeded
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn PlayerRecords) -> Result<(), ErrorTypePlayerHandler>) -> Result<(), ErrorTypePlayerHandler> {
+        let mut records = self.clone();
+        f(&mut records)?;
+        *self = records;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "file_store")]
+impl FilePlayerStore {
+    // Nothing is read until load, a missing file is an empty store
+    pub fn new(path: impl Into<PathBuf>, format: PlayerStoreFormat) -> Self {
+        FilePlayerStore {
+            path: path.into(),
+            format,
+            records: MemoryPlayerStore::new(),
+        }
+    }
+
+    // Changes a copy of the records and only keeps it once the file is written, so memory and file never disagree
+    fn apply(
+        &mut self,
+        change: impl FnOnce(&mut MemoryPlayerStore) -> Result<(), ErrorTypePlayerHandler>,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let mut records = self.records.clone();
+        change(&mut records)?;
+        self.save(&records)?;
+        self.records = records;
+        Ok(())
+    }
+
+    // Writes next to the file under its full name plus .tmp and renames over it, a crash part way through leaves the previous file readable
+    fn save(&self, records: &MemoryPlayerStore) -> Result<(), ErrorTypePlayerHandler> {
+        let contents = match self.format {
+            PlayerStoreFormat::Json => serde_json::to_string_pretty(records)
+                .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("FilePlayerStore::save: serializing failed Error: [{}]", e)))?,
+            PlayerStoreFormat::Ron => ron::ser::to_string_pretty(records, ron::ser::PrettyConfig::default())
+                .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("FilePlayerStore::save: serializing failed Error: [{}]", e)))?,
+        };
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        fs::write(&tmp_path, contents)
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("FilePlayerStore::save: writing [{}] failed Error: [{}]", tmp_path.display(), e)))?;
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("FilePlayerStore::save: replacing [{}] failed Error: [{}]", self.path.display(), e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "file_store")]
+impl PlayerRecords for FilePlayerStore {
+    fn insert_player(&mut self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.insert_player(player))
+    }
+
+    fn remove_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.remove_player(player_uuid))
+    }
+
+    fn remove_all_players(&mut self) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.remove_all_players())
+    }
+
+    fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler> {
+        self.records.list_players()
+    }
+
+    fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        self.records.main_player()
+    }
+
+    fn set_main_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.set_main_player(player_uuid))
+    }
+
+    fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler> {
+        self.records.player_profile(player_uuid)
+    }
+
+    fn set_profile_value(&mut self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.set_profile_value(player_uuid, key, value))
+    }
+
+    fn remove_profile_value(&mut self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.remove_profile_value(player_uuid, key))
+    }
+
+    fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler> {
+        self.records.player_stats(player_uuid)
+    }
+
+    fn write_player_stats(&mut self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.write_player_stats(batch))
+    }
+
+    fn begin_session(&mut self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler> {
+        let mut session_id = None;
+        self.apply(|records| {
+            session_id = Some(records.begin_session(players)?);
+            Ok(())
+        })?;
+        session_id.ok_or_else(|| ErrorTypePlayerHandler::DBActionFailed("FilePlayerStore::begin_session failed: no session id".to_string()))
+    }
+
+    fn add_session_player(&mut self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.add_session_player(session_id, seat, player_uuid))
+    }
+
+    fn end_session(&mut self, session_id: i64, players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.end_session(session_id, players))
+    }
+
+    fn save_party_preset(&mut self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.save_party_preset(name, members))
+    }
+
+    fn delete_party_preset(&mut self, name: &str) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| records.delete_party_preset(name))
+    }
+
+    fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler> {
+        self.records.party_presets()
+    }
+}
+
+#[cfg(feature = "file_store")]
+impl PlayerStore for FilePlayerStore {
+    fn load(&mut self) -> Result<(), PlayerHandlerError> {
+        if !self.path.exists() {
+            self.records = MemoryPlayerStore::new();
+            return Ok(());
+        }
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(format!("FilePlayerStore::load: reading [{}] failed Error: [{}]", self.path.display(), e)))?;
+        self.records = match self.format {
+            PlayerStoreFormat::Json => serde_json::from_str(&contents)
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("FilePlayerStore::load: parsing [{}] failed Error: [{}]", self.path.display(), e)))?,
+            PlayerStoreFormat::Ron => ron::from_str(&contents)
+                .map_err(|e| ErrorTypePlayerHandler::DBQueryMappingFailed(format!("FilePlayerStore::load: parsing [{}] failed Error: [{}]", self.path.display(), e)))?,
+        };
+        Ok(())
+    }
+
+    // The whole transaction is saved with one write of the file
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn PlayerRecords) -> Result<(), ErrorTypePlayerHandler>) -> Result<(), ErrorTypePlayerHandler> {
+        self.apply(|records| f(records))
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy::tasks::{
+    block_on,
+    IoTaskPool,
+    TaskPool,
+};
+
+use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+
+use std::sync::{
+    atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+    Arc,
+};
+
+use crate::{
+    DBPlayer,
+    Party,
+    PartyEvent,
+    PlayerDbWrite,
+    PlayerDbWriteMessage,
+    PlayerDbWriteResult,
+    PlayerDbWriter,
+    PlayerRecordInserted,
+    PlayerRecords,
+    PlayerStorage,
+};
+
+impl PlayerDbWriter {
+    // Starts the worker on the IoTaskPool, it stops once the writer is dropped and everything queued has been applied
+    pub fn spawn(storage: &PlayerStorage) -> Self {
+        let (sender, receiver) = async_channel::unbounded();
+        let (result_sender, results) = async_channel::unbounded();
+        let pending = Arc::new(AtomicUsize::new(0));
+        IoTaskPool::get_or_init(TaskPool::new)
+            .spawn(run_player_db_worker(storage.clone(), receiver.clone(), result_sender.clone(), pending.clone()))
+            .detach();
+
+        PlayerDbWriter {
+            sender,
+            results,
+            pending,
+            #[cfg(target_arch = "wasm32")]
+            receiver,
+            #[cfg(target_arch = "wasm32")]
+            result_sender,
+            #[cfg(target_arch = "wasm32")]
+            storage: storage.clone(),
+        }
+    }
+
+    pub fn queue(
+        &self,
+        write: PlayerDbWrite,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        if let Err(e) = self.sender.try_send(PlayerDbWriteMessage::Write(write)) {
+            self.pending.fetch_sub(1, Ordering::SeqCst);
+            return Err(ErrorTypePlayerHandler::DBActionFailed(format!("PlayerDbWriter::queue failed: the worker has stopped Error: [{}]", e)));
+        }
+        Ok(())
+    }
+
+    pub fn pending(
+        &self,
+    ) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    // Blocks until every write queued before the call has been committed or has failed, returns right away when nothing is pending
+    pub fn flush(
+        &self,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        if self.pending() == 0 {
+            return Ok(());
+        }
+        self.flush_queued()
+    }
+
+    // Without bevy's multi_threaded feature the worker is a task on this thread's local executor, so the executor runs while flush waits instead of waiting on itself
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush_queued(
+        &self,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let (done_sender, done_receiver) = async_channel::bounded(1);
+        self.sender
+            .try_send(PlayerDbWriteMessage::Flush(done_sender))
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("PlayerDbWriter::flush failed: the worker has stopped Error: [{}]", e)))?;
+        IoTaskPool::get()
+            .with_local_executor(|executor| block_on(executor.run(done_receiver.recv())))
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(format!("PlayerDbWriter::flush failed: the worker has stopped Error: [{}]", e)))
+    }
+
+    // The browser thread can not block, so the writes still queued are taken off the channel and applied here.
+    // The worker shares the thread and applies each batch without yielding, so it is never part way through one
+    #[cfg(target_arch = "wasm32")]
+    fn flush_queued(
+        &self,
+    ) -> Result<(), ErrorTypePlayerHandler> {
+        let mut batch = Vec::new();
+        let mut flushes = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                PlayerDbWriteMessage::Write(write) => batch.push(write),
+                PlayerDbWriteMessage::Flush(done) => flushes.push(done),
+            }
+        }
+        finish_player_db_batch(&self.storage, batch, flushes, &self.result_sender, &self.pending);
+        Ok(())
+    }
+
+    fn drain_results(
+        &self,
+    ) -> Vec<PlayerDbWriteResult> {
+        let mut results = Vec::new();
+        while let Ok(result) = self.results.try_recv() {
+            results.push(result);
+        }
+        results
+    }
+}
+
+// Waits for the first queued message, then takes everything queued behind it as one batch
+async fn run_player_db_worker(
+    storage: PlayerStorage,
+    receiver: async_channel::Receiver<PlayerDbWriteMessage>,
+    results: async_channel::Sender<PlayerDbWriteResult>,
+    pending: Arc<AtomicUsize>,
+) {
+    while let Ok(message) = receiver.recv().await {
+        let mut batch = Vec::new();
+        let mut flushes = Vec::new();
+        let mut next = Some(message);
+        while let Some(message) = next {
+            match message {
+                PlayerDbWriteMessage::Write(write) => batch.push(write),
+                PlayerDbWriteMessage::Flush(done) => flushes.push(done),
+            }
+            next = receiver.try_recv().ok();
+        }
+        finish_player_db_batch(&storage, batch, flushes, &results, &pending);
+    }
+}
+
+fn finish_player_db_batch(
+    storage: &PlayerStorage,
+    batch: Vec<PlayerDbWrite>,
+    flushes: Vec<async_channel::Sender<()>>,
+    results: &async_channel::Sender<PlayerDbWriteResult>,
+    pending: &AtomicUsize,
+) {
+    for (write, result) in apply_player_db_writes(storage, batch) {
+        // The results receiver lives as long as the writer, once it is gone nobody is listening
+        let _ = results.try_send(PlayerDbWriteResult {
+            write,
+            result,
+        });
+        pending.fetch_sub(1, Ordering::SeqCst);
+    }
+    for done in flushes {
+        let _ = done.try_send(());
+    }
+}
+
+// The batch shares one transaction, when that fails each write is retried in its own so one bad write does not roll back the rest
+fn apply_player_db_writes(
+    storage: &PlayerStorage,
+    batch: Vec<PlayerDbWrite>,
+) -> Vec<(PlayerDbWrite, Result<(), ErrorTypePlayerHandler>)> {
+    if batch.is_empty() {
+        return Vec::new();
+    }
+    let batch_result = storage.with_transaction(|records| {
+        for write in batch.iter() {
+            apply_player_db_write(records, write)?;
+        }
+        Ok(())
+    });
+    match batch_result {
+        Ok(()) => batch.into_iter().map(|write| (write, Ok(()))).collect(),
+        Err(e) if batch.len() == 1 => batch.into_iter().zip(std::iter::once(Err(e))).collect(),
+        Err(_) => batch
+            .into_iter()
+            .map(|write| {
+                let result = storage.with_transaction(|records| apply_player_db_write(records, &write));
+                (write, result)
+            })
+            .collect(),
+    }
+}
+
+fn apply_player_db_write(
+    records: &mut dyn PlayerRecords,
+    write: &PlayerDbWrite,
+) -> Result<(), ErrorTypePlayerHandler> {
+    match write {
+        PlayerDbWrite::InsertPlayer { player_uuid, email, username, player_type } => records.insert_player(&DBPlayer::new(player_uuid, email.as_ref(), username.as_ref(), player_type.clone())),
+    }
+}
+
+// Sends a PlayerDbWriteResult for each finished write, committed inserts also go out as PlayerRecordInserted
+pub fn report_player_db_writes(
+    db_writer: Res<PlayerDbWriter>,
+    mut party: ResMut<Party>,
+    mut write_results: EventWriter<PlayerDbWriteResult>,
+) {
+    for write_result in db_writer.drain_results() {
+        match (&write_result.write, &write_result.result) {
+            (PlayerDbWrite::InsertPlayer { player_uuid, player_type, .. }, Ok(())) => {
+                party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                    player_uuid: *player_uuid,
+                    player_type: player_type.clone(),
+                }));
+            },
+            (write, Err(e)) => warn!("report_player_db_writes -> [{:?}] Failed: [{:?}]", write, e),
+        }
+        write_results.send(write_result);
+    }
+}
+
+// Last still sees an AppExit sent earlier in the frame
+pub fn flush_player_db_writes_on_exit(
+    db_writer: Res<PlayerDbWriter>,
+    mut app_exit: EventReader<AppExit>,
+) {
+    if app_exit.read().count() == 0 {
+        return;
+    }
+    if let Err(e) = db_writer.flush() {
+        warn!("flush_player_db_writes_on_exit -> db_writer.flush Error: {:?}", e);
+    }
+}
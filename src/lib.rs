@@ -1,46 +1,179 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::{
+        component::{ComponentHooks, ComponentId, StorageType},
+        query::QueryData,
+        system::SystemParam,
+        world::DeferredWorld,
+    },
+    prelude::*,
+    state::app::StatesPlugin,
+};
 
 use bevy_easy_shared_definitions::{
     DatabaseConnection, 
     ErrorTypePlayerHandler,
 };
 
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::env;
+use std::env::VarError;
+#[cfg(feature = "file_store")]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::AtomicUsize;
+use std::time::Duration;
+
+use dotenv::dotenv;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use uuid::Uuid;
 
 pub mod database;
 pub mod handlers;
 
+use database::migrations::PlayerTableMigrationContext;
+
+use handlers::input_binding::{
+    join_party_on_gamepad_press,
+    release_input_bindings,
+};
+use handlers::profile::load_player_profiles;
+use handlers::remote::{
+    poll_remote_transport,
+    update_remote_connections,
+};
+use handlers::requests::handle_player_requests;
+use handlers::session::{
+    begin_player_session,
+    track_player_session,
+};
+
+use handlers::stats::{
+    flush_player_stats,
+    load_player_stats,
+};
+use handlers::writer::{
+    flush_player_db_writes_on_exit,
+    report_player_db_writes,
+};
+
 pub mod prelude {
     pub use crate::{
+        ActivePlayerChanged,
+        AddAiPlayerRequest,
+        AddLocalPlayerRequest,
         BevyEasyPlayerHandlerPlugin,
+        KeyboardLayout,
+        LoadPlayerFromDbRequest,
+        LoopbackTransport,
+        MainPlayerChanged,
+        MemoryPlayerStore,
         Party,
+        PartyChange,
+        PartyDelta,
+        PartyPresetLoad,
+        PartyReordered,
+        PartySeat,
+        PartySeatingMode,
+        PartySlots,
+        PartySnapshot,
+        PartySnapshotPlayer,
+        PlayerHandler,
+        PlayerHandlerError,
         PlayerHandlerInterface,
+        PlayerHandlerSet,
+        PlayerHandlerStartupError,
+        PlayerHandlerState,
+        PlayerBundle,
         PlayerComponent,
+        PlayerData,
+        PlayerDbTx,
+        PlayerDbWrite,
+        PlayerDbWriteResult,
+        PlayerDbWriter,
+        PlayerEmail,
+        PlayerId,
+        PlayerIndex,
+        PlayerInputBinding,
+        PlayerInputHandler,
+        PlayerJoinedParty,
+        PlayerKind,
+        PlayerLeftParty,
+        PlayerName,
+        PlayerPresetHandler,
+        PlayerProfile,
+        PlayerProfileHandler,
+        PlayerRecordDeleted,
+        PlayerRecordInserted,
+        PlayerRequest,
+        PlayerRequestResult,
+        PlayerSession,
+        PlayerStats,
+        PlayerRecords,
+        PlayerStorage,
+        PlayerStore,
+        PlayerStoreBackend,
+        PresetLoadQueue,
+        ProfileSelection,
+        ProfileValue,
+        RemoteConnectionState,
+        RemotePeerEvent,
+        RemotePlayerTransport,
+        RemoteTransport,
+        RemovePlayerRequest,
+        ResetRosterRequest,
+        SqlitePlayerStore,
+        TestRefPlayerMode,
+    };
+    #[cfg(feature = "file_store")]
+    pub use crate::{
+        FilePlayerStore,
+        PlayerStoreFormat,
     };
 }
 
 #[derive(Clone, Resource)]
 pub struct BevyEasyPlayerHandlerPlugin {
+    gamepad_join_button: Option<GamepadButtonType>,
     main_player_email: Option<String>,
     main_player_username: Option<String>,
     main_player_uuid: Option<Uuid>,
     party_size: Option<usize>,
+    player_store: PlayerStoreBackend,
+    profile_selection: bool,
+    remote_grace_period: Duration,
+    seating_mode: PartySeatingMode,
+    stats_flush_interval: Duration,
+    test_ref_player: TestRefPlayerMode,
+    write_behind: bool,
 }
 
 impl BevyEasyPlayerHandlerPlugin {
     pub fn init() -> Self {
         BevyEasyPlayerHandlerPlugin {
+            gamepad_join_button: None,
             main_player_email: None,
             main_player_username: None,
             main_player_uuid: None,
             party_size: None,
+            player_store: PlayerStoreBackend::Sqlite,
+            profile_selection: false,
+            remote_grace_period: Duration::from_secs(10),
+            seating_mode: PartySeatingMode::Compact,
+            stats_flush_interval: Duration::from_secs(30),
+            test_ref_player: TestRefPlayerMode::Disabled,
+            write_behind: false,
         }
     }
 
+    // Opt-in "press start to join", an unbound gamepad pressing the button joins as a new local player
+    pub fn gamepad_join(mut self, join_button: GamepadButtonType) -> Self {
+        self.gamepad_join_button = Some(join_button);
+        self
+    }
+
     pub fn main_player_email(mut self, main_player_email: &str) -> Self {
         self.main_player_email = Some(String::from(main_player_email));
         self
@@ -61,6 +194,56 @@ impl BevyEasyPlayerHandlerPlugin {
         self
     }
 
+    // Where player records are kept, the host app's DatabaseConnection unless another backend is chosen
+    pub fn player_store(mut self, player_store: PlayerStoreBackend) -> Self {
+        self.player_store = player_store;
+        self
+    }
+
+    // Opt-in profile selection, startup waits in ProfileSelection::Selecting until a local profile is picked with select_profile
+    pub fn profile_selection(mut self) -> Self {
+        self.profile_selection = true;
+        self
+    }
+
+    // How long a disconnected remote keeps their party slot before they are removed
+    pub fn remote_grace_period(mut self, remote_grace_period: Duration) -> Self {
+        self.remote_grace_period = remote_grace_period;
+        self
+    }
+
+    pub fn seating_mode(mut self, seating_mode: PartySeatingMode) -> Self {
+        self.seating_mode = seating_mode;
+        self
+    }
+
+    // How often changed PlayerStats are written to player_stats, they are always written on AppExit
+    pub fn stats_flush_interval(mut self, stats_flush_interval: Duration) -> Self {
+        self.stats_flush_interval = stats_flush_interval;
+        self
+    }
+
+    pub fn test_ref_player(mut self, test_ref_uuid: &Uuid, test_ref_username: &str, test_ref_email: &str) -> Self {
+        self.test_ref_player = TestRefPlayerMode::Enabled {
+            uuid: *test_ref_uuid,
+            username: String::from(test_ref_username),
+            email: String::from(test_ref_email),
+        };
+        self
+    }
+
+    pub fn test_ref_player_mode(mut self, test_ref_player: TestRefPlayerMode) -> Self {
+        self.test_ref_player = test_ref_player;
+        self
+    }
+
+    // Opt-in write-behind, the record inserts on_player_component_spawned makes go through the PlayerDbWriter instead of blocking the frame.
+    // Only those inserts are deferred, removals, resets, profiles and main player changes are still written right away, after a flush
+    pub fn write_behind(mut self) -> Self {
+        self.write_behind = true;
+        self
+    }
+
     pub fn build(mut self) -> BevyEasyPlayerHandlerPlugin {
         if self.main_player_uuid.is_none() {
             self.main_player_uuid = Some(Uuid::now_v7());
@@ -77,13 +260,25 @@ impl BevyEasyPlayerHandlerPlugin {
         self.party_size = Some(party_size);
 
         Self {
+            gamepad_join_button: self.gamepad_join_button,
             main_player_email: self.main_player_email,
             main_player_username: self.main_player_username,
             main_player_uuid: self.main_player_uuid,
             party_size: self.party_size,
+            player_store: self.player_store,
+            profile_selection: self.profile_selection,
+            remote_grace_period: self.remote_grace_period,
+            seating_mode: self.seating_mode,
+            stats_flush_interval: self.stats_flush_interval,
+            test_ref_player: self.test_ref_player,
+            write_behind: self.write_behind,
         }
     }
 
+    pub fn get_gamepad_join_button(&self) -> Result<Option<&GamepadButtonType>, ErrorTypePlayerHandler> {
+        Ok(self.gamepad_join_button.as_ref())
+    }
+
     pub fn get_main_player_email(&self) -> Result<Option<&String>, ErrorTypePlayerHandler> {
         Ok(self.main_player_email.as_ref())
     }
@@ -100,6 +295,42 @@ impl BevyEasyPlayerHandlerPlugin {
         Ok(self.party_size.as_ref())
     }
 
+    pub fn get_player_store_backend(&self) -> Result<&PlayerStoreBackend, ErrorTypePlayerHandler> {
+        Ok(&self.player_store)
+    }
+
+    pub fn get_profile_selection(&self) -> Result<bool, ErrorTypePlayerHandler> {
+        Ok(self.profile_selection)
+    }
+
+    pub fn get_remote_grace_period(&self) -> Result<&Duration, ErrorTypePlayerHandler> {
+        Ok(&self.remote_grace_period)
+    }
+
+    pub fn get_party_seating_mode(&self) -> Result<&PartySeatingMode, ErrorTypePlayerHandler> {
+        Ok(&self.seating_mode)
+    }
+
+    // Highest seat a player can be placed in, FixedSeats stops at the party size and Compact at the seat after the last player
+    pub fn get_seat_limit(&self, player_map: &PartySlots) -> Result<usize, ErrorTypePlayerHandler> {
+        match self.seating_mode {
+            PartySeatingMode::Compact => Ok(player_map.len() + 1),
+            PartySeatingMode::FixedSeats => Ok(self.party_size.unwrap_or(1)),
+        }
+    }
+
+    pub fn get_stats_flush_interval(&self) -> Result<&Duration, ErrorTypePlayerHandler> {
+        Ok(&self.stats_flush_interval)
+    }
+
+    pub fn get_test_ref_player_mode(&self) -> Result<&TestRefPlayerMode, ErrorTypePlayerHandler> {
+        Ok(&self.test_ref_player)
+    }
+
+    pub fn get_write_behind(&self) -> Result<bool, ErrorTypePlayerHandler> {
+        Ok(self.write_behind)
+    }
+
     pub fn set_party_size_limit(&mut self, party_size: usize) -> Result<(), ErrorTypePlayerHandler> {
         self.party_size = Some(party_size);
         Ok(())
@@ -112,34 +343,177 @@ impl BevyEasyPlayerHandlerPlugin {
     }
 }
 
+// Compact closes the gap a leaving player opens, FixedSeats keeps every player in their seat so seat N stays tied to controller N
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum PartySeatingMode {
+    #[default]
+    Compact,
+    FixedSeats,
+}
+
+// The test reference player is an optional sentinel record that pipelines refuse to load into, or remove from, the party.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestRefPlayerMode {
+    Disabled,
+    Enabled {
+        uuid: Uuid,
+        username: String,
+        email: String,
+    },
+}
+
+impl TestRefPlayerMode {
+    // Opt-in loader for TEST_REF_PLAYER_UUID / TEST_REF_PLAYER_USERNAME / TEST_REF_PLAYER_EMAIL from the environment or a .env file
+    pub fn from_env() -> Result<TestRefPlayerMode, ErrorTypePlayerHandler> {
+        dotenv().ok();
+        let read_var = |key: &str| -> Result<String, ErrorTypePlayerHandler> {
+            match env::var(key) {
+                Ok(value) => Ok(value),
+                Err(VarError::NotPresent) => Err(ErrorTypePlayerHandler::VarErrorNotPresent),
+                Err(VarError::NotUnicode(err)) => Err(ErrorTypePlayerHandler::VarErrorNotUnicode(err.to_string_lossy().into_owned())),
+            }
+        };
+        let test_ref_uuid_string = read_var("TEST_REF_PLAYER_UUID")?;
+        let username = read_var("TEST_REF_PLAYER_USERNAME")?;
+        let email = read_var("TEST_REF_PLAYER_EMAIL")?;
+        let uuid = match Uuid::try_parse(test_ref_uuid_string.as_str()) {
+            Ok(uuid) => uuid,
+            Err(e) => return Err(ErrorTypePlayerHandler::UuidParsingFailed(e.to_string())),
+        };
+        Ok(TestRefPlayerMode::Enabled {
+            uuid,
+            username,
+            email,
+        })
+    }
+}
+
 impl Plugin for BevyEasyPlayerHandlerPlugin {
     fn build(&self, app: &mut App) { // Builds automatically on .add_plugins() call
-        // Ensure the database connection exists as a resource
-        if !app.world().contains_resource::<DatabaseConnection>() {
-            panic!("ERROR: [ DatabaseConnection ] resource is missing. Ensure the host app provides it.");
+        // Only the SQLite backend needs the host app to provide the database connection
+        match &self.player_store {
+            PlayerStoreBackend::Sqlite => {
+                if !app.world().contains_resource::<DatabaseConnection>() {
+                    panic!("ERROR: [ DatabaseConnection ] resource is missing. Ensure the host app provides it.");
+                }
+                let test_ref_uuid = match &self.test_ref_player {
+                    TestRefPlayerMode::Enabled { uuid, .. } => Some(*uuid),
+                    TestRefPlayerMode::Disabled => None,
+                };
+                let store = SqlitePlayerStore::new(app.world().resource::<DatabaseConnection>()).test_ref_uuid(test_ref_uuid);
+                app.insert_resource(PlayerStorage::new(store));
+            },
+            PlayerStoreBackend::Memory => { app.insert_resource(PlayerStorage::new(MemoryPlayerStore::new())); },
+            #[cfg(feature = "file_store")]
+            PlayerStoreBackend::File { path, format } => { app.insert_resource(PlayerStorage::new(FilePlayerStore::new(path.clone(), *format))); },
+            PlayerStoreBackend::Custom => {
+                if !app.world().contains_resource::<PlayerStorage>() {
+                    panic!("ERROR: [ PlayerStorage ] resource is missing. Insert it before adding the plugin when using PlayerStoreBackend::Custom.");
+                }
+            },
         }
 
         // Insert the plugin itself and other resources into the host app
         app.insert_resource(self.clone());
         app.insert_resource(PlayerHandlerInterface::get());
         app.insert_resource(Party::new());
+        app.insert_resource(PlayerIndex::default());
+        app.insert_resource(PlayerSession::default());
+        app.insert_resource(PresetLoadQueue::default());
+        let db_writer = PlayerDbWriter::spawn(app.world().resource::<PlayerStorage>());
+        app.insert_resource(db_writer);
+
+        // Without profile selection the main player comes from the plugin settings, so startup begins already Selected
+        if !app.is_plugin_added::<StatesPlugin>() {
+            app.add_plugins(StatesPlugin);
+        }
+        app.insert_state(match self.profile_selection {
+            true => ProfileSelection::Selecting,
+            false => ProfileSelection::Selected,
+        });
+        app.init_state::<PlayerHandlerState>();
+        app.insert_resource(PlayerHandlerStartupError::default());
+        app.configure_sets(Update, (PlayerHandlerSet::Requests, PlayerHandlerSet::Roster, PlayerHandlerSet::Startup).chain());
+
+        // Register the party and roster events
+        app.add_event::<PlayerJoinedParty>();
+        app.add_event::<PlayerLeftParty>();
+        app.add_event::<ActivePlayerChanged>();
+        app.add_event::<MainPlayerChanged>();
+        app.add_event::<PlayerRecordInserted>();
+        app.add_event::<PlayerRecordDeleted>();
+        app.add_event::<PartyReordered>();
+
+        // Register the request events and their result
+        app.add_event::<AddLocalPlayerRequest>();
+        app.add_event::<AddAiPlayerRequest>();
+        app.add_event::<RemovePlayerRequest>();
+        app.add_event::<LoadPlayerFromDbRequest>();
+        app.add_event::<ResetRosterRequest>();
+        app.add_event::<PlayerRequestResult>();
+        app.add_event::<PlayerDbWriteResult>();
 
         // Add the startup protocol system
-        app.add_systems(Startup, PlayerHandlerInterface::start_up_protocol);
-        app.add_systems(Update, on_player_component_spawned);
-        app.add_systems(Update, on_player_component_removal);
+        app.add_systems(Startup, PlayerHandlerInterface::start_up_protocol.in_set(PlayerHandlerSet::Startup));
+        app.add_systems(Update, handle_player_requests.in_set(PlayerHandlerSet::Requests));
+        app.add_systems(Update, on_player_component_spawned.in_set(PlayerHandlerSet::Roster));
+        app.add_systems(Update, on_player_component_removal.in_set(PlayerHandlerSet::Roster));
+        app.add_systems(Update, sync_party_seats.after(on_player_component_spawned).after(on_player_component_removal).in_set(PlayerHandlerSet::Roster));
+        app.add_systems(Update, release_input_bindings.after(on_player_component_spawned).after(on_player_component_removal).in_set(PlayerHandlerSet::Roster));
+        app.add_systems(Update, load_player_profiles.after(on_player_component_spawned).in_set(PlayerHandlerSet::Roster));
+        app.add_systems(Update, load_player_stats.after(on_player_component_spawned).in_set(PlayerHandlerSet::Roster));
+        // Last still sees an AppExit sent earlier in the frame
+        app.add_systems(Last, flush_player_stats);
+        // Remote players, the grace period runs on the app's Time
+        app.init_resource::<Time>();
+        app.add_systems(Update, poll_remote_transport.in_set(PlayerHandlerSet::Requests));
+        app.add_systems(Update, update_remote_connections.after(poll_remote_transport).in_set(PlayerHandlerSet::Requests));
+
+        if self.gamepad_join_button.is_some() {
+            app.add_systems(Update, join_party_on_gamepad_press.in_set(PlayerHandlerSet::Requests));
+        }
         app.add_systems(Update, sync_plugin_party_main_player_uuid);
-        app.add_systems(Update, PlayerHandlerInterface::start_up_protocol_finish.run_if(run_once()));
+        // Finishing moves PlayerHandlerState out of Initializing, so it runs once
+        app.add_systems(Update, PlayerHandlerInterface::start_up_protocol_finish.in_set(PlayerHandlerSet::Startup).run_if(in_state(ProfileSelection::Selected).and_then(in_state(PlayerHandlerState::Initializing))));
+        app.add_systems(Update, begin_player_session.after(PlayerHandlerInterface::start_up_protocol_finish).in_set(PlayerHandlerSet::Startup).run_if(in_state(ProfileSelection::Selected).and_then(run_once())));
+        app.add_systems(Last, track_player_session.after(flush_player_stats));
+        app.add_systems(PostUpdate, send_party_events);
+        // Write-behind results turn into record events, and nothing queued is left behind on exit
+        app.add_systems(PostUpdate, report_player_db_writes.before(send_party_events));
+        app.add_systems(Last, flush_player_db_writes_on_exit);
     }
 }
 
-// System to trigger when PlayerComponent is spawned
+// System to send the events queued on the Party during the frame
+pub fn send_party_events(
+    mut party: ResMut<Party>,
+    mut writers: PartyEventWriters,
+) {
+    if party.pending_events.is_empty() {
+        return;
+    }
+    for event in party.drain_pending_events() {
+        match event {
+            PartyEvent::PlayerJoinedParty(event) => { writers.player_joined.send(event); },
+            PartyEvent::PlayerLeftParty(event) => { writers.player_left.send(event); },
+            PartyEvent::ActivePlayerChanged(event) => { writers.active_player_changed.send(event); },
+            PartyEvent::MainPlayerChanged(event) => { writers.main_player_changed.send(event); },
+            PartyEvent::PlayerRecordInserted(event) => { writers.record_inserted.send(event); },
+            PartyEvent::PlayerRecordDeleted(event) => { writers.record_deleted.send(event); },
+            PartyEvent::PartyReordered(event) => { writers.party_reordered.send(event); },
+        }
+    }
+}
+
+// System to trigger when PlayerComponent is removed
 fn on_player_component_removal(
     mut commands: Commands,
-    db: Res<DatabaseConnection>,
+    storage: Res<PlayerStorage>,
     mut party: ResMut<Party>,
     phi: ResMut<PlayerHandlerInterface>,
-    player_query: Query<&PlayerComponent>,
+    player_index: Res<PlayerIndex>,
+    plugin: Res<BevyEasyPlayerHandlerPlugin>,
     mut removed: RemovedComponents<PlayerComponent>,
 ) {
     let mut removed_event: bool = false;
@@ -147,62 +521,17 @@ fn on_player_component_removal(
         removed_event = true;
     }
     if removed_event {
-        let index_minus_one = match party.get_active_player_index() {
-            Ok(value) => value - 1,
-            Err(_) => {
-                panic!("on_player_component_removal -> match party.get_active_player_index failed...");
-            },
-        };
-        match party.set_active_player_index(index_minus_one) {
-            Ok(()) => (),
-            Err(_) => warn!("on_player_component_removal -> party.set_active_player_index(index_minus_one) failed..."),
-        };
-        let player_map_ids = match party.get_player_map_clone() {
-            Ok(result) => result,
-            Err(_) => {
-                panic!("on_player_component_removal -> match party.get_player_map_clone failed...");
-            },
+        let seating_mode = match plugin.get_party_seating_mode() {
+            Ok(seating_mode) => *seating_mode,
+            Err(_) => PartySeatingMode::Compact,
         };
-        println!("player_map_ids [{:?}]", &player_map_ids);
-        let party_size = player_map_ids.len();
-        println!("party_size [{:?}]", &party_size);
-        let mut player_vec_ids: Vec<(usize, Uuid)> = Vec::new();
-        for n in 0..party_size {
-            let ref_uuid = match player_map_ids.get(&( n + 1 )) {
-                Some(value) => value.to_owned(),
-                None => {
-                    panic!("on_player_component_removal -> match player_map_ids.get(&n) failed...");
-                },
-            };
-            println!("loop player_vec_ids [{}]::[{}]", ( n + 1 ), &ref_uuid);
-            player_vec_ids.push((( n + 1 ), ref_uuid));
-        }
-        for player in player_query.iter() {
-            let player_mutex = match player.player.lock() {
-                Ok(result) => result,
-                Err(_) => {
-                    panic!("on_player_component_removal -> match match player.player.lock failed...");
-                },
-            };
-            let player_uuid = match player_mutex.get_player_id() {
-                Ok(value) => value.to_owned(),
-                Err(_) => {
-                    panic!("on_player_component_removal -> match player_mutex.get_player_id() failed...");
-                },
-            };
-            println!("before: player_vec_ids [{:?}]", &player_vec_ids);
-            player_vec_ids = player_vec_ids
-                .into_iter()
-                .filter_map(|(usize, player)| 
-                    if player == player_uuid {
-                        return None; 
-                    } else {
-                        return Some((usize, player));
-                    } 
-                )
-                .collect();
-            println!("after: player_vec_ids [{:?}]", &player_vec_ids);
-        }
+        // Seated players whose entity is gone
+        let player_vec_ids: Vec<(usize, Uuid)> = party.player_map
+            .iter()
+            .filter(|(_, player_uuid)| !player_index.contains(player_uuid))
+            .map(|(seat, player_uuid)| (seat, *player_uuid))
+            .collect();
+        debug!("on_player_component_removal: player_vec_ids [{:?}]", &player_vec_ids);
         let main_player_uuid = match party.get_main_player_uuid() {
             Ok(result) => result,
             Err(_) => {
@@ -211,11 +540,22 @@ fn on_player_component_removal(
                 Some(uuid)
             },
         };
+        let active_before = party.active_player;
+        let mut seat_opened = false;
+        let mut seats_opened_up_to_active: usize = 0;
         for player in player_vec_ids.iter() {
             if Some(player.1) != main_player_uuid {
-                party.player_map.remove(&player.0);
+                party.player_map.take(&player.0);
+                seat_opened = true;
+                if player.0 <= active_before {
+                    seats_opened_up_to_active += 1;
+                }
+                party.queue_event(PartyEvent::PlayerLeftParty(PlayerLeftParty {
+                    player_uuid: player.1,
+                    party_index: player.0,
+                }));
             } else {
-                match phi.pipeline_db_and_party_add_main_player_from_db_to_party(&mut commands, &db, &player.1) {
+                match phi.pipeline_db_and_party_add_main_player_from_db_to_party(&mut commands, &storage, &player.1, &plugin) {
                     Ok(()) => (),
                     Err(_) => {
                         warn!("on_player_component_removal -> match party.get_main_player_uuid failed...");
@@ -223,119 +563,154 @@ fn on_player_component_removal(
                 }
             }
         }
+        match seating_mode {
+            PartySeatingMode::Compact => {
+                if seat_opened && party.player_map.compact() {
+                    let player_map = party.player_map.clone();
+                    party.queue_event(PartyEvent::PartyReordered(PartyReordered { player_map }));
+                }
+                // Step back one seat for each seat opened at or before the active one, seat 1 is as low as the active player goes. Only seats opened here count, player_map_remove_player steps back for its own removals.
+                // It runs after the collapse so the ActiveChanged delta names a seat that is taken by then
+                if seat_opened {
+                    let active_index = active_before.saturating_sub(seats_opened_up_to_active).max(1);
+                    match party.set_active_player_index(active_index) {
+                        Ok(()) => (),
+                        Err(_) => warn!("on_player_component_removal -> party.set_active_player_index(active_index) failed..."),
+                    };
+                }
+            },
+            PartySeatingMode::FixedSeats => {
+                // Fixed seats keep the active seat, an emptied one falls back to the nearest taken seat before it
+                let active_index = party.active_player;
+                if party.player_map.get(&active_index).is_none() {
+                    let fallback_index = party.player_map
+                        .iter()
+                        .map(|(seat, _)| seat)
+                        .filter(|seat| *seat < active_index)
+                        .last()
+                        .unwrap_or(1);
+                    match party.set_active_player_index(fallback_index) {
+                        Ok(()) => (),
+                        Err(_) => warn!("on_player_component_removal -> party.set_active_player_index(fallback_index) failed..."),
+                    };
+                }
+            },
+        }
     }
 }
 
 // System to trigger when PlayerComponent is spawned
 pub fn on_player_component_spawned(
     mut party: ResMut<Party>,
-    db: Res<DatabaseConnection>,
-    entity_player_query: Query<(Entity, &PlayerComponent)>,
+    storage: Res<PlayerStorage>,
     listen_query: Query<Entity, Added<PlayerComponent>>,
-    player_query: Query<&PlayerComponent>,
-    phi: ResMut<PlayerHandlerInterface>,
+    player_query: Query<PlayerData>,
+    plugin: Res<BevyEasyPlayerHandlerPlugin>,
+    db_writer: Res<PlayerDbWriter>,
 ) {
-    let mut target_ent: Option<Entity> = None; 
-    for entity in listen_query.iter() {
-        println!("PlayerComponent init: entity {:?}", entity);
-        let target_idx = party.get_player_count_party(&player_query).unwrap();
-        match party.set_active_player_index(target_idx) {
-            Ok(result) => result,
+    // A snapshot or several joins in one frame spawn more than one player, each of them is seated and stored
+    for target in listen_query.iter() {
+        let player = match player_query.get(target) {
+            Ok(player) => player,
             Err(e) => {
-                warn!("on_player_component_spawned -> party.active_player_set [{}] Error: {:?}", target_idx, e);
-                ()
+                warn!("Failed: on_player_component_spawned -> player_query.get [{:?}] Error: [{:?}]", target, e);
+                continue;
             },
         };
-        target_ent = Some(entity);
-    };
-    if target_ent.is_some() {
-        let target = target_ent.unwrap();
-        let mut username: Option<String> = None;
-        let mut user_uuid: Option<Uuid> = None;
-        let mut player_type: Option<PlayerType> = None;
-        for (entity, player) in entity_player_query.iter() {
-            if &target == &entity {
-                let player_data: Arc<Mutex<dyn Player + Send>> = player.player.clone();
-                let player_mutex = player_data.lock().unwrap();
-
-                let player_username = match player_mutex.get_player_username() {
-                    Ok(email) => email.to_owned(),
-                    Err(_) => {
-                        warn!("on_player_component_spawned -> match player_mutex.get_player_username Failed: Spawning Failed UserName");
-                        String::from("Username Fetch Failed")
-                    },
-                };
-                username = Some(player_username);
-
-                let player_uuid = match player_mutex.get_player_id() {
-                    Ok(uuid) => uuid.to_owned(),
-                    Err(_) => {
-                        warn!("on_player_component_spawned -> match player_mutex.get_player_id Failed: Spawning random Uuid");
-                        Uuid::now_v7()
-                    },
-                };
-                user_uuid = Some(player_uuid);
-
-                let p_type = match player_mutex.get_player_type() {
-                    Ok(player_type) => player_type.to_owned(),
-                    Err(_) => {
-                        warn!("on_player_component_spawned -> match player_mutex.get_player_type Failed: Spawning random Uuid");
-                        PlayerType::PlayerLocal
-                    },
-                };
-                player_type = Some(p_type);
-
-                drop(player_mutex);
+        let username = match &player.name.0 {
+            Some(username) => username.to_owned(),
+            None => {
+                warn!("on_player_component_spawned -> match player.name Failed: Spawning Failed UserName");
+                String::from("Username Fetch Failed")
+            },
+        };
+        let player_uuid = player.id.0;
+        let player_type = player.kind.0.clone();
 
-                let map_entry_exists = match party.verify_player_exists_player_map_uuid(&player_uuid) {
-                    Ok(status) => status,
-                    Err(e) => {
-                        warn!("Failed: on_player_component_spawned -> match party.players_add_player Error: [{:?}]", e);
-                        false
-                    },
-                };
-                if !map_entry_exists {
-                    let party_size = party.player_map.len();
-                    let party_size_plus_one = party_size + 1;
-                    party.player_map.insert(party_size_plus_one, player_uuid);
-                }
+        let map_entry_exists = match party.verify_player_exists_player_map_uuid(&player_uuid) {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("Failed: on_player_component_spawned -> match party.players_add_player Error: [{:?}]", e);
+                false
+            },
+        };
+        if !map_entry_exists {
+            let open_seat = party.player_map.first_open_seat();
+            let seated = plugin
+                .get_seat_limit(&party.player_map)
+                .and_then(|seat_limit| party.player_map.insert(open_seat, player_uuid, seat_limit));
+            match seated {
+                Ok(_) => {
+                    party.record_delta(PartyChange::Joined {
+                        player_uuid,
+                        seat: open_seat,
+                        player_type: player_type.clone(),
+                        username: player.name.0.clone(),
+                    });
+                },
+                Err(e) => warn!("on_player_component_spawned -> party.player_map.insert [{}] Error: {:?}", open_seat, e),
             }
         }
-
-        if username.is_none() {
-            warn!("Failed: on_player_component_spawned -> username.is_none()");
+        let party_index = party.player_map.seat_of(&player_uuid);
+        if let Some(party_index) = party_index {
+            party.queue_event(PartyEvent::PlayerJoinedParty(PlayerJoinedParty {
+                player_uuid,
+                party_index,
+            }));
+            // The new player becomes active in whichever seat they took, players seated ahead of their spawn by a snapshot leave the active seat alone
+            if !map_entry_exists {
+                if let Err(e) = party.set_active_player_index(party_index) {
+                    warn!("on_player_component_spawned -> party.set_active_player_index [{}] Error: {:?}", party_index, e);
+                }
+            }
         }
 
-        if player_type.is_none() {
-            warn!("Failed: on_player_component_spawned -> username.is_none()");
+        if player_type == PlayerType::PlayerMain { // Main player records and their marker are written by the startup and reset pipelines
+            continue;
         }
-
-        if user_uuid.is_none() {
-            warn!("Failed: on_player_component_spawned -> username.is_none()");
+        // With write-behind the worker commits the record, report_player_db_writes sends PlayerRecordInserted once it has
+        if let Ok(true) = plugin.get_write_behind() {
+            if let Err(e) = db_writer.queue(PlayerDbWrite::InsertPlayer {
+                player_uuid,
+                email: Some(username.clone()),
+                username: Some(username),
+                player_type,
+            }) {
+                warn!("Failed: on_player_component_spawned -> db_writer.queue Error: [{:?}]", e);
+            }
+            continue;
         }
-
-        let party_size = match party.get_player_count_party(&player_query) {
-            Ok(usize) => usize,
-            Err(e) => {
-                warn!("Failed: on_player_component_spawned -> match party.get_player_count_party Error: [{:?}]", e);
-                0
-            },
-        };
-        match party.set_active_player_index(party_size) {
-            Ok(status) => status,
-            Err(e) => {
-                warn!("Failed: on_player_component_spawned -> match party.set_active_player_index Error: [{:?}]", e);
-                ()
+        match storage.insert_player(&DBPlayer::new(&player_uuid, Some(&username), Some(&username), player_type.clone())) {
+            Ok(()) => {
+                party.queue_event(PartyEvent::PlayerRecordInserted(PlayerRecordInserted {
+                    player_uuid,
+                    player_type,
+                }));
             },
-        };
-        let username = username.unwrap(); 
-        match phi.action_insert_player_record(&db, &user_uuid.unwrap(), Some(&username), Some(&username), player_type.unwrap()) {
-            Ok(status) => status,
             Err(e) => {
-                warn!("Failed: on_player_component_spawned -> match phi.action_insert_player_record Error: [{:?}]", e);
+                warn!("Failed: on_player_component_spawned -> match storage.insert_player Error: [{:?}]", e);
             },
         };
-        ()
+    }
+}
+
+// Keeps each player's PartySeat in step with Party::player_map
+pub fn sync_party_seats(
+    mut commands: Commands,
+    party: Res<Party>,
+    seat_query: Query<(Entity, &PlayerId, Option<&PartySeat>)>,
+    unseated_query: Query<(), (With<PlayerId>, Without<PartySeat>)>,
+) {
+    if !party.is_changed() && unseated_query.is_empty() {
+        return;
+    }
+    for (entity, player_id, party_seat) in seat_query.iter() {
+        match (party.player_map.seat_of(&player_id.0), party_seat) {
+            (Some(seat), Some(party_seat)) if party_seat.0 == seat => (),
+            (Some(seat), _) => { commands.entity(entity).insert(PartySeat(seat)); },
+            (None, Some(_)) => { commands.entity(entity).remove::<PartySeat>(); },
+            (None, None) => (),
+        }
     }
 }
 
@@ -356,24 +731,550 @@ pub fn sync_plugin_party_main_player_uuid(
             }
         }
     };
-    party.main_player_uuid = Some(new_uuid);
+    if party.main_player_uuid != Some(new_uuid) {
+        match party.set_main_player_uuid(&new_uuid) {
+            Ok(_) => (),
+            Err(e) => warn!("sync_plugin_party_main_player_uuid -> match party.set_main_player_uuid failed... [{:?}]", e),
+        };
+    }
 }
 
 #[derive(Resource)]
 pub struct PlayerHandlerInterface {}
 
+// One transaction on the locked DatabaseConnection, PlayerHandlerInterface::with_db_tx hands it out and commits or rolls it back as a unit
+pub struct PlayerDbTx<'conn> {
+    tx: rusqlite::Transaction<'conn>,
+}
+
+// Bundles the resources and queries the Party and PlayerHandlerInterface calls expect, so systems can take a single parameter
+#[derive(SystemParam)]
+pub struct PlayerHandler<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub storage: Res<'w, PlayerStorage>,
+    pub entity_player_query: Query<'w, 's, (Entity, &'static PlayerId)>,
+    pub party: ResMut<'w, Party>,
+    pub phi: Res<'w, PlayerHandlerInterface>,
+    pub player_index: Res<'w, PlayerIndex>,
+    pub player_query: Query<'w, 's, PlayerData>,
+    pub plugin: ResMut<'w, BevyEasyPlayerHandlerPlugin>,
+    pub db_writer: Res<'w, PlayerDbWriter>,
+}
+
+// PlayerHandler plus the profile selection state, for stored profile values and the local profiles picked at startup.
+// It holds a PlayerHandler, so a system takes one or the other
+#[derive(SystemParam)]
+pub struct PlayerProfileHandler<'w, 's> {
+    pub handler: PlayerHandler<'w, 's>,
+    pub profile_selection: Res<'w, State<ProfileSelection>>,
+    pub next_profile_selection: ResMut<'w, NextState<ProfileSelection>>,
+}
+
+// PlayerHandler plus the queue load_preset fills for handle_player_requests
+#[derive(SystemParam)]
+pub struct PlayerPresetHandler<'w, 's> {
+    pub handler: PlayerHandler<'w, 's>,
+    pub preset_load_queue: ResMut<'w, PresetLoadQueue>,
+}
+
+// PlayerHandler plus the current bindings, which bind_input checks a new binding against
+#[derive(SystemParam)]
+pub struct PlayerInputHandler<'w, 's> {
+    pub handler: PlayerHandler<'w, 's>,
+    pub input_binding_query: Query<'w, 's, (&'static PlayerId, &'static PlayerInputBinding)>,
+}
+
+// One writer per PartyEvent variant, send_party_events hands each drained event to its writer
+#[derive(SystemParam)]
+pub struct PartyEventWriters<'w> {
+    pub player_joined: EventWriter<'w, PlayerJoinedParty>,
+    pub player_left: EventWriter<'w, PlayerLeftParty>,
+    pub active_player_changed: EventWriter<'w, ActivePlayerChanged>,
+    pub main_player_changed: EventWriter<'w, MainPlayerChanged>,
+    pub record_inserted: EventWriter<'w, PlayerRecordInserted>,
+    pub record_deleted: EventWriter<'w, PlayerRecordDeleted>,
+    pub party_reordered: EventWriter<'w, PartyReordered>,
+}
+
+// One reader per request event handle_player_requests listens to
+#[derive(SystemParam)]
+pub struct PlayerRequestReaders<'w, 's> {
+    pub add_local: EventReader<'w, 's, AddLocalPlayerRequest>,
+    pub add_ai: EventReader<'w, 's, AddAiPlayerRequest>,
+    pub remove: EventReader<'w, 's, RemovePlayerRequest>,
+    pub load: EventReader<'w, 's, LoadPlayerFromDbRequest>,
+    pub reset: EventReader<'w, 's, ResetRosterRequest>,
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct DBPlayer {
     pub uuid: String,
     pub email: String,
     pub username: String,
+    pub player_type: PlayerType,
+}
+
+// A session_table row and everyone who sat in the party during it, with the seat they joined in
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DBSession {
+    pub session_id: i64,
+    pub started_at: i64,
+    pub ended_at: Option<i64>,
+    pub players: Vec<(usize, Uuid)>,
+}
+
+// A party_preset row with the seat each member had when it was saved
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct DBPartyPreset {
+    pub name: String,
+    pub created_at: i64,
+    pub members: Vec<(usize, Uuid)>,
+}
+
+// What load_preset did with each member of the preset
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartyPresetLoad {
+    pub name: String,
+    pub queued: Vec<Uuid>,
+    pub already_seated: Vec<Uuid>,
+    pub missing: Vec<Uuid>,
+}
+
+// Players waiting to be loaded from a preset, handle_player_requests takes them all the next frame as LoadPlayerFromDb requests
+#[derive(Debug, Default, Resource)]
+pub struct PresetLoadQueue {
+    pub pending: VecDeque<Uuid>,
+}
+
+// Initializing until start_up_protocol_finish has seated and synced the main player, then Ready. Failed keeps the error in PlayerHandlerStartupError
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum PlayerHandlerState {
+    #[default]
+    Initializing,
+    Ready,
+    Failed,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct PlayerHandlerStartupError {
+    pub error: Option<PlayerHandlerError>,
+}
+
+// ErrorTypePlayerHandler lives in bevy_easy_shared_definitions, failures callers need to tell apart get their own variant here
+#[derive(Debug)]
+pub enum PlayerHandlerError {
+    // The database was written by a newer build, found is its user_version and supported is PLAYER_TABLE_SCHEMA_VERSION
+    SchemaTooNew {
+        found: i32,
+        supported: i32,
+    },
+    // No player record matches the uuid
+    PlayerNotFound(Uuid),
+    Handler(ErrorTypePlayerHandler),
+}
+
+impl From<ErrorTypePlayerHandler> for PlayerHandlerError {
+    fn from(error: ErrorTypePlayerHandler) -> Self {
+        PlayerHandlerError::Handler(error)
+    }
+}
+
+impl From<PlayerHandlerError> for ErrorTypePlayerHandler {
+    fn from(error: PlayerHandlerError) -> Self {
+        match error {
+            PlayerHandlerError::SchemaTooNew { found, supported } => ErrorTypePlayerHandler::DBActionFailed(format!(
+                "database schema version [{}] is newer than the supported version [{}]",
+                found,
+                supported,
+            )),
+            PlayerHandlerError::PlayerNotFound(player_uuid) => ErrorTypePlayerHandler::DBQueryFailed(format!(
+                "no player record matches [{}]",
+                player_uuid,
+            )),
+            PlayerHandlerError::Handler(error) => error,
+        }
+    }
+}
+
+// Order game systems against the plugin in Update with these, they run Requests, then Roster, then Startup.
+// Startup also holds start_up_protocol in the Startup schedule
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SystemSet)]
+pub enum PlayerHandlerSet {
+    // Request events, remote transport and gamepad join, the systems that add and remove players
+    Requests,
+    // Seats spawned players, clears removed ones and loads their bindings, profile and stats
+    Roster,
+    Startup,
+}
+
+// Selecting holds back start_up_protocol_finish and the session until select_profile picks the main player
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum ProfileSelection {
+    #[default]
+    Selecting,
+    Selected,
+}
+
+// The session this run is recording, opened once the startup protocol finishes and closed on AppExit
+#[derive(Debug, Default, Resource)]
+pub struct PlayerSession {
+    pub session_id: Option<i64>,
 }
 
 #[derive(Resource)]
 pub struct Party {
     pub active_player: usize,
     pub main_player_uuid: Option<Uuid>,
-    pub player_map: HashMap<usize, Uuid>,
+    pub player_map: PartySlots,
+    pub pending_events: Vec<PartyEvent>,
+    pub revision: u64,
+    pub delta_log: Vec<PartyDelta>,
+}
+
+// Ordered party seats, seat n lives in slots[n - 1] and None marks an open seat
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PartySlots {
+    slots: Vec<Option<Uuid>>,
+}
+
+// A plain copy of the roster for sending to clients or writing to a save slot. Emails stay on the machine that owns them
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PartySnapshot {
+    pub revision: u64,
+    pub active_player: usize,
+    pub main_player_uuid: Option<Uuid>,
+    pub players: Vec<PartySnapshotPlayer>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PartySnapshotPlayer {
+    pub seat: usize,
+    pub player_uuid: Uuid,
+    pub player_type: PlayerType,
+    pub username: Option<String>,
+}
+
+// ----- [ Party and roster events ] ----- //
+
+// Mutations queue a PartyEvent on the Party, send_party_events forwards them as the typed events below in PostUpdate
+#[derive(Clone, Debug, PartialEq)]
+pub enum PartyEvent {
+    PlayerJoinedParty(PlayerJoinedParty),
+    PlayerLeftParty(PlayerLeftParty),
+    ActivePlayerChanged(ActivePlayerChanged),
+    MainPlayerChanged(MainPlayerChanged),
+    PlayerRecordInserted(PlayerRecordInserted),
+    PlayerRecordDeleted(PlayerRecordDeleted),
+    PartyReordered(PartyReordered),
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct PlayerJoinedParty {
+    pub player_uuid: Uuid,
+    pub party_index: usize,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct PlayerLeftParty {
+    pub player_uuid: Uuid,
+    pub party_index: usize,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct ActivePlayerChanged {
+    pub previous_index: usize,
+    pub active_index: usize,
+    pub player_uuid: Option<Uuid>,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct MainPlayerChanged {
+    pub previous_uuid: Option<Uuid>,
+    pub main_player_uuid: Uuid,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct PlayerRecordInserted {
+    pub player_uuid: Uuid,
+    pub player_type: PlayerType,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct PlayerRecordDeleted {
+    pub player_uuid: Uuid,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct PartyReordered {
+    pub player_map: PartySlots,
+}
+
+// ----- [ Party deltas ] ----- //
+
+// Each roster change is stamped with the party revision it produced, so clients can replay them in order and spot a missing one
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PartyDelta {
+    pub revision: u64,
+    pub change: PartyChange,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum PartyChange {
+    Joined {
+        player_uuid: Uuid,
+        seat: usize,
+        player_type: PlayerType,
+        username: Option<String>,
+    },
+    Left {
+        player_uuid: Uuid,
+        seat: usize,
+    },
+    Renamed {
+        player_uuid: Uuid,
+        username: Option<String>,
+    },
+    Reordered {
+        player_map: PartySlots,
+    },
+    ActiveChanged {
+        active_player: usize,
+    },
+}
+
+// ----- [ Request events ] ----- //
+
+// Game code sends these with an EventWriter, handle_player_requests runs the matching pipeline and replies with a PlayerRequestResult
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct AddLocalPlayerRequest {
+    pub username: String,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct AddAiPlayerRequest {
+    pub username: String,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct RemovePlayerRequest {
+    pub uuid: Uuid,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct LoadPlayerFromDbRequest {
+    pub uuid: Uuid,
+}
+
+#[derive(Clone, Debug, Event, PartialEq)]
+pub struct ResetRosterRequest;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerRequest {
+    AddLocalPlayer(AddLocalPlayerRequest),
+    AddAiPlayer(AddAiPlayerRequest),
+    RemovePlayer(RemovePlayerRequest),
+    LoadPlayerFromDb(LoadPlayerFromDbRequest),
+    ResetRoster(ResetRosterRequest),
+}
+
+#[derive(Debug, Event)]
+pub struct PlayerRequestResult {
+    pub request: PlayerRequest,
+    pub result: Result<(), PlayerHandlerError>,
+}
+
+// A record change for the write-behind worker. Only the spawn inserts are deferred, removals and main player changes stay synchronous behind a flush
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerDbWrite {
+    InsertPlayer {
+        player_uuid: Uuid,
+        email: Option<String>,
+        username: Option<String>,
+        player_type: PlayerType,
+    },
+}
+
+// Sent for every queued write once the worker has committed it or given up on it
+#[derive(Debug, Event)]
+pub struct PlayerDbWriteResult {
+    pub write: PlayerDbWrite,
+    pub result: Result<(), ErrorTypePlayerHandler>,
+}
+
+enum PlayerDbWriteMessage {
+    Write(PlayerDbWrite),
+    Flush(async_channel::Sender<()>),
+}
+
+// Handle to the write-behind worker running on the IoTaskPool, pending counts the writes it has not finished yet
+#[derive(Resource)]
+pub struct PlayerDbWriter {
+    sender: async_channel::Sender<PlayerDbWriteMessage>,
+    results: async_channel::Receiver<PlayerDbWriteResult>,
+    pending: Arc<AtomicUsize>,
+    // flush applies the queued writes itself on wasm, where it can not wait for the worker
+    #[cfg(target_arch = "wasm32")]
+    receiver: async_channel::Receiver<PlayerDbWriteMessage>,
+    #[cfg(target_arch = "wasm32")]
+    result_sender: async_channel::Sender<PlayerDbWriteResult>,
+    #[cfg(target_arch = "wasm32")]
+    storage: PlayerStorage,
+}
+
+// ----- [ Remote players ] ----- //
+
+// Lifecycle of a remote peer's party slot. Disconnected players keep their slot until the grace period runs out
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub enum RemoteConnectionState {
+    Connecting,
+    Connected,
+    Disconnected {
+        elapsed: Duration,
+    },
+    TimedOut,
+    Dropped,
+}
+
+// What a transport reports about its peers, the peer is identified by the player uuid it plays as
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemotePeerEvent {
+    Connecting {
+        player_uuid: Uuid,
+        username: Option<String>,
+        player_type: PlayerType,
+    },
+    Connected {
+        player_uuid: Uuid,
+    },
+    Disconnected {
+        player_uuid: Uuid,
+    },
+    Left {
+        player_uuid: Uuid,
+    },
+}
+
+// Implemented by a networking crate to feed peer joins and leaves into the party
+pub trait RemoteTransport: Send + Sync + 'static {
+    fn poll_events(&mut self) -> Vec<RemotePeerEvent>;
+}
+
+#[derive(Resource)]
+pub struct RemotePlayerTransport {
+    transport: Box<dyn RemoteTransport>,
+}
+
+// In-memory transport, clones share one queue so a test can hold a handle and send events through it
+#[derive(Clone, Default)]
+pub struct LoopbackTransport {
+    queue: Arc<Mutex<VecDeque<RemotePeerEvent>>>,
+}
+
+// The player records and what hangs off them, a PlayerStore provides them directly and hands them out inside with_transaction.
+// Removing a player also removes their profile and stats, profile and stat writes for a player without a record are refused or skipped
+pub trait PlayerRecords {
+    fn insert_player(&mut self, player: &DBPlayer) -> Result<(), ErrorTypePlayerHandler>;
+    fn remove_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler>;
+    fn remove_all_players(&mut self) -> Result<(), ErrorTypePlayerHandler>;
+    fn list_players(&self) -> Result<Vec<DBPlayer>, ErrorTypePlayerHandler>;
+    fn main_player(&self) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler>;
+    fn set_main_player(&mut self, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler>;
+    fn player_profile(&self, player_uuid: &Uuid) -> Result<PlayerProfile, ErrorTypePlayerHandler>;
+    fn set_profile_value(&mut self, player_uuid: &Uuid, key: &str, value: ProfileValue) -> Result<(), ErrorTypePlayerHandler>;
+    fn remove_profile_value(&mut self, player_uuid: &Uuid, key: &str) -> Result<(), ErrorTypePlayerHandler>;
+    fn player_stats(&self, player_uuid: &Uuid) -> Result<PlayerStats, ErrorTypePlayerHandler>;
+    // Rows for players without a record are skipped
+    fn write_player_stats(&mut self, batch: &[(Uuid, String, i64)]) -> Result<(), ErrorTypePlayerHandler>;
+    // Returns the new session id
+    fn begin_session(&mut self, players: &[(usize, Uuid)]) -> Result<i64, ErrorTypePlayerHandler>;
+    fn add_session_player(&mut self, session_id: i64, seat: usize, player_uuid: &Uuid) -> Result<(), ErrorTypePlayerHandler>;
+    fn end_session(&mut self, session_id: i64, players: &[Uuid]) -> Result<(), ErrorTypePlayerHandler>;
+    // Saving under a name that is already taken replaces that preset
+    fn save_party_preset(&mut self, name: &str, members: &[(usize, Uuid)]) -> Result<(), ErrorTypePlayerHandler>;
+    fn delete_party_preset(&mut self, name: &str) -> Result<(), ErrorTypePlayerHandler>;
+    fn party_presets(&self) -> Result<Vec<DBPartyPreset>, ErrorTypePlayerHandler>;
+    fn get_player(&self, player_uuid: &Uuid) -> Result<Option<DBPlayer>, ErrorTypePlayerHandler> {
+        let player_uuid = player_uuid.to_string();
+        Ok(self.list_players()?.into_iter().find(|player| player.get_uuid_string() == &player_uuid))
+    }
+    fn player_exists(&self, player_uuid: &Uuid) -> Result<bool, ErrorTypePlayerHandler> {
+        Ok(self.get_player(player_uuid)?.is_some())
+    }
+    fn party_preset(&self, name: &str) -> Result<Option<DBPartyPreset>, ErrorTypePlayerHandler> {
+        Ok(self.party_presets()?.into_iter().find(|preset| preset.name == name))
+    }
+}
+
+// Where player records, profiles, stats, sessions and presets live between runs. Everything the plugin stores goes through the PlayerStorage holding one of these
+pub trait PlayerStore: PlayerRecords + Send + Sync + 'static {
+    // Runs once at startup before the store is read, the SQLite store migrates its schema and a file backed store reads its file here
+    fn load(&mut self) -> Result<(), PlayerHandlerError> {
+        Ok(())
+    }
+    // Runs f once against the records, nothing it changed is kept when it returns an error
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn PlayerRecords) -> Result<(), ErrorTypePlayerHandler>) -> Result<(), ErrorTypePlayerHandler>;
+}
+
+// Clones share one store, so the write-behind worker and the systems work on the same records
+#[derive(Clone, Resource)]
+pub struct PlayerStorage {
+    store: Arc<Mutex<Box<dyn PlayerStore>>>,
+}
+
+// Picks the PlayerStore the plugin builds, Custom uses the PlayerStorage resource the host app inserted before adding the plugin
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PlayerStoreBackend {
+    #[default]
+    Sqlite,
+    Memory,
+    #[cfg(feature = "file_store")]
+    File {
+        path: PathBuf,
+        format: PlayerStoreFormat,
+    },
+    Custom,
+}
+
+#[cfg(feature = "file_store")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerStoreFormat {
+    Json,
+    Ron,
+}
+
+// The player_table records on a DatabaseConnection, each call outside with_transaction runs in its own transaction
+pub struct SqlitePlayerStore {
+    conn: Arc<Mutex<rusqlite::Connection>>,
+    migration_context: PlayerTableMigrationContext,
+}
+
+// Records kept in memory only, for unit tests and headless servers that do not keep players between runs. Profiles and stats are keyed by the player's uuid string
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MemoryPlayerStore {
+    players: Vec<DBPlayer>,
+    main_player_uuid: Option<String>,
+    profiles: BTreeMap<String, PlayerProfile>,
+    stats: BTreeMap<String, BTreeMap<String, i64>>,
+    sessions: Vec<DBSession>,
+    presets: Vec<DBPartyPreset>,
+}
+
+// A MemoryPlayerStore saved to a human-readable RON or JSON file after every change
+#[cfg(feature = "file_store")]
+pub struct FilePlayerStore {
+    path: PathBuf,
+    format: PlayerStoreFormat,
+    records: MemoryPlayerStore,
 }
 
 pub trait Player { //  ->  
@@ -387,12 +1288,157 @@ pub trait Player { //  ->
     fn set_player_username(&mut self, new_username: &str) -> Result<(), ErrorTypePlayerHandler>;
 }
 
-#[derive(Clone, Component)]
-pub struct PlayerComponent {
-    pub player: Arc<Mutex<dyn Player + Send>>,
+// Marker for party member entities, the player data lives in the components below
+#[derive(Clone, Component, Debug, Default)]
+pub struct PlayerComponent;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PlayerId(pub Uuid);
+
+// PlayerId keeps the PlayerIndex in step through its hooks, so a replaced id or a despawn never leaves a stale entry behind
+impl Component for PlayerId {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(on_player_id_inserted)
+            .on_remove(on_player_id_removed);
+    }
+}
+
+fn on_player_id_inserted(mut world: DeferredWorld, entity: Entity, _component_id: ComponentId) {
+    let Some(player_id) = world.get::<PlayerId>(entity).copied() else {
+        return;
+    };
+    if let Some(mut player_index) = world.get_resource_mut::<PlayerIndex>() {
+        player_index.insert(entity, player_id.0);
+    }
+}
+
+fn on_player_id_removed(mut world: DeferredWorld, entity: Entity, _component_id: ComponentId) {
+    if let Some(mut player_index) = world.get_resource_mut::<PlayerIndex>() {
+        player_index.remove_entity(entity);
+    }
+}
+
+// Wins, play time, xp, high scores and the like. Changes are batched and written by flush_player_stats, a player leaving the world writes theirs on the way out
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    values: BTreeMap<String, i64>,
+    dirty: BTreeSet<String>,
+}
+
+impl Component for PlayerStats {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(on_player_stats_removed);
+    }
+}
+
+// The unsaved values are handed to a command, the store write needs the PlayerStorage resource
+fn on_player_stats_removed(mut world: DeferredWorld, entity: Entity, _component_id: ComponentId) {
+    let Some(player_id) = world.get::<PlayerId>(entity).copied() else {
+        return;
+    };
+    let Some(stats) = world.get::<PlayerStats>(entity) else {
+        return;
+    };
+    let batch: Vec<(Uuid, String, i64)> = stats
+        .dirty_values()
+        .into_iter()
+        .map(|(stat, value)| (player_id.0, stat, value))
+        .collect();
+    if batch.is_empty() {
+        return;
+    }
+    world.commands().add(move |world: &mut World| {
+        let Some(storage) = world.get_resource::<PlayerStorage>() else {
+            return;
+        };
+        if let Some(db_writer) = world.get_resource::<PlayerDbWriter>() {
+            if let Err(e) = db_writer.flush() {
+                warn!("on_player_stats_removed -> db_writer.flush Error: {:?}", e);
+                return;
+            }
+        }
+        if let Err(e) = storage.write_player_stats(&batch) {
+            warn!("on_player_stats_removed -> storage.write_player_stats Error: {:?}", e);
+        }
+    });
+}
+
+// The device driving a local player, dropped again when the player leaves the party
+#[derive(Clone, Component, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PlayerInputBinding {
+    Gamepad(Gamepad),
+    Keyboard(KeyboardLayout),
+}
+
+// Keyboard regions, so two players can share one keyboard
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyboardLayout {
+    Full,
+    Wasd,
+    Arrows,
+}
+
+// Seat the player holds in Party::player_map, kept current by sync_party_seats
+#[derive(Clone, Component, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PartySeat(pub usize);
+
+// uuid <-> entity lookup for party members, maintained by the PlayerId component hooks
+#[derive(Debug, Default, Resource)]
+pub struct PlayerIndex {
+    entities: HashMap<Uuid, Entity>,
+    uuids: HashMap<Entity, Uuid>,
+}
+
+#[derive(Clone, Component, Debug, PartialEq)]
+pub struct PlayerKind(pub PlayerType);
+
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub struct PlayerName(pub Option<String>);
+
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+pub struct PlayerEmail(pub Option<String>);
+
+// Mirrors the player's player_profile rows, loaded when the player joins the party
+#[derive(Clone, Component, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PlayerProfile {
+    values: BTreeMap<String, ProfileValue>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ProfileValue {
+    Bool(bool),
+    Integer(i64),
+    Real(f64),
+    Text(String),
+}
+
+#[derive(Bundle, Clone)]
+pub struct PlayerBundle {
+    pub marker: PlayerComponent,
+    pub id: PlayerId,
+    pub kind: PlayerKind,
+    pub name: PlayerName,
+    pub email: PlayerEmail,
+}
+
+#[derive(QueryData)]
+pub struct PlayerData {
+    pub entity: Entity,
+    pub id: &'static PlayerId,
+    pub kind: &'static PlayerKind,
+    pub name: &'static PlayerName,
+    pub email: &'static PlayerEmail,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum PlayerType {
     PlayerAiLocal,
     PlayerAiRemote,
@@ -403,6 +1449,7 @@ pub enum PlayerType {
 }
 
 #[derive(Clone, Component, Debug, Resource)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PlayerAiLocal {
     player_email: Option<String>,
     player_uuid: Uuid,
@@ -411,6 +1458,7 @@ pub struct PlayerAiLocal {
 }
 
 #[derive(Clone, Component, Debug, Resource)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PlayerAiRemote {
     player_email: Option<String>,
     player_uuid: Uuid,
@@ -419,6 +1467,7 @@ pub struct PlayerAiRemote {
 }
 
 #[derive(Clone, Component, Debug, Resource)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PlayerLocal {
     player_email: Option<String>,
     player_uuid: Uuid,
@@ -427,6 +1476,7 @@ pub struct PlayerLocal {
 }
 
 #[derive(Clone, Component, Debug, Resource)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PlayerMain {
     player_email: Option<String>,
     player_uuid: Uuid,
@@ -435,6 +1485,7 @@ pub struct PlayerMain {
 }
 
 #[derive(Clone, Component, Debug, Resource)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct PlayerRemote {
     player_email: Option<String>,
     player_uuid: Uuid,
@@ -1,7 +1,19 @@
 #[cfg(test)]
 mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+    };
     use bevy_easy_player_handler::*;
-    use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+    use bevy_easy_player_handler::database::migrations::{
+        PlayerTableMigrationContext,
+        PLAYER_TABLE_SCHEMA_VERSION,
+    };
+    use bevy_easy_shared_definitions::{
+        DatabaseConnection,
+        ErrorTypePlayerHandler,
+    };
+    use std::time::Duration;
     use uuid::Uuid;
 
     const PLAYER_EMAIL: &str = "test@example.com";
@@ -14,6 +26,19 @@ mod tests {
     // const PLAYER_TYPE_MAIN: PlayerType = PlayerType::PlayerMain;
     // const PLAYER_TYPE_REMOTE: PlayerType = PlayerType::PlayerRemote;
 
+    fn world_with_memory_db() -> World {
+        let mut world: World = World::new();
+        world.insert_resource(DatabaseConnection::new(":memory:"));
+        world.insert_resource(PlayerHandlerInterface::get());
+        world
+    }
+
+    fn run_migrations(world: &mut World) -> Result<i32, ErrorTypePlayerHandler> {
+        world.run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.action_run_migrations(&db).map_err(ErrorTypePlayerHandler::from)
+        })
+    }
+
     #[test]
     fn test_database_start_up_protocol() -> Result<(), ErrorTypePlayerHandler> {
         todo!();
@@ -39,14 +64,1005 @@ mod tests {
             uuid: String::from(new_uuid.clone()),
             email: String::from(PLAYER_EMAIL),
             username: String::from(PLAYER_USERNAME),
+            player_type: PlayerType::PlayerAiRemote,
         };
         let ref_uuid = dbplayer.get_uuid_string();
         let ref_email = dbplayer.get_email_string();
         let ref_username = dbplayer.get_username_string();
+        let ref_player_type = dbplayer.get_player_type();
 
         assert_eq!(ref_uuid, &String::from(new_uuid.clone()));
         assert_eq!(ref_email, &String::from(PLAYER_EMAIL));
         assert_eq!(ref_username, &String::from(PLAYER_USERNAME));
+        assert_eq!(ref_player_type, &PlayerType::PlayerAiRemote);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_type_db_code() -> Result<(), ErrorTypePlayerHandler> {
+        let player_types = vec![
+            PlayerType::PlayerAiLocal,
+            PlayerType::PlayerAiRemote,
+            PlayerType::PlayerLocal,
+            PlayerType::PlayerMain,
+            PlayerType::PlayerRemote,
+            PlayerType::PlayerTestRef,
+        ];
+        for player_type in player_types {
+            let code = player_type.to_db_code();
+            assert_eq!(PlayerType::from_db_code(code)?, player_type);
+        }
+        assert!(PlayerType::from_db_code(0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_migrations_fresh_database() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+
+        // Running twice proves the runner is idempotent
+        assert_eq!(run_migrations(&mut world)?, PLAYER_TABLE_SCHEMA_VERSION);
+        assert_eq!(run_migrations(&mut world)?, PLAYER_TABLE_SCHEMA_VERSION);
+
+        let table_exists = world.run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_table_player_exists(&db)
+        })?;
+        assert!(table_exists);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_migrations_legacy_database() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+        let test_ref_uuid = Uuid::now_v7();
+        let main_uuid = Uuid::now_v7();
+        let local_uuid = Uuid::now_v7();
+        let ai_uuid = Uuid::now_v7();
+        {
+            let db = world.resource::<DatabaseConnection>();
+            let conn = db.get_connection();
+            let conn = conn.lock().unwrap();
+            conn.execute_batch("CREATE TABLE player_table (uuid TEXT PRIMARY KEY, email BLOB, username BLOB)").unwrap();
+            conn.execute("INSERT INTO player_table (uuid, email, username) VALUES (?1, ?2, ?3)", (String::from(main_uuid), PLAYER_EMAIL, PLAYER_USERNAME)).unwrap();
+            conn.execute("INSERT INTO player_table (uuid, email, username) VALUES (?1, ?2, ?3)", (String::from(test_ref_uuid), PLAYER_EMAIL, "test_ref")).unwrap();
+            conn.execute("INSERT INTO player_table (uuid, email, username) VALUES (?1, ?2, ?3)", (String::from(local_uuid), PLAYER_EMAIL, "PlayerLocal")).unwrap();
+            conn.execute("INSERT INTO player_table (uuid, email, username) VALUES (?1, ?2, ?3)", (String::from(ai_uuid), PLAYER_EMAIL, "PlayerAiLocal")).unwrap();
+        }
+
+        let version = world.run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.action_run_migrations_with_context(&db, &PlayerTableMigrationContext { test_ref_uuid: Some(test_ref_uuid) })
+        })?;
+        assert_eq!(version, PLAYER_TABLE_SCHEMA_VERSION);
+
+        // Only the configured test reference and the AI username are retyped, row order is not a hint
+        let players = world.run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_existing_players(&db)
+        })?;
+        assert_eq!(players.len(), 4);
+        for player in players.iter() {
+            let expected = if player.get_uuid_string() == &String::from(test_ref_uuid) {
+                PlayerType::PlayerTestRef
+            } else if player.get_uuid_string() == &String::from(ai_uuid) {
+                PlayerType::PlayerAiLocal
+            } else {
+                PlayerType::PlayerLocal
+            };
+            assert_eq!(player.get_player_type(), &expected);
+        }
+
+        let main_exists = world.run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_main_player_exists(&db)
+        })?;
+        assert!(!main_exists);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_migrations_newer_database() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+        {
+            let db = world.resource::<DatabaseConnection>();
+            let conn = db.get_connection();
+            let conn = conn.lock().unwrap();
+            conn.execute_batch(&format!("PRAGMA user_version = {}", PLAYER_TABLE_SCHEMA_VERSION + 1)).unwrap();
+        }
+
+        let result = world.run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.action_run_migrations(&db)
+        });
+        match result {
+            Err(PlayerHandlerError::SchemaTooNew { found, supported }) => {
+                assert_eq!(found, PLAYER_TABLE_SCHEMA_VERSION + 1);
+                assert_eq!(supported, PLAYER_TABLE_SCHEMA_VERSION);
+            },
+            other => panic!("expected SchemaTooNew, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_main_player_marker() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+        run_migrations(&mut world)?;
+
+        let first_uuid = Uuid::now_v7();
+        let second_uuid = Uuid::now_v7();
+        let (main_before, main_first, main_second) = world.run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            let email = String::from(PLAYER_EMAIL);
+            let username = String::from(PLAYER_USERNAME);
+            phi.action_insert_player_record(&db, &first_uuid, Some(&email), Some(&username), PlayerType::PlayerMain)?;
+            phi.action_insert_player_record(&db, &second_uuid, Some(&email), Some(&username), PlayerType::PlayerMain)?;
+            let main_before = phi.query_db_main_player_exists(&db)?;
+
+            phi.action_set_main_player_record(&db, &second_uuid)?;
+            let main_first = phi.query_db_main_player(&db)?;
+
+            phi.action_set_main_player_record(&db, &first_uuid)?;
+            let main_second = phi.query_db_main_player(&db)?;
+
+            // Marking a missing record fails and leaves the current main player untouched
+            assert!(phi.action_set_main_player_record(&db, &Uuid::now_v7()).is_err());
+            assert_eq!(phi.query_db_main_player(&db)?.get_uuid_string(), &String::from(first_uuid));
+            Ok::<_, ErrorTypePlayerHandler>((main_before, main_first, main_second))
+        })?;
+
+        assert!(!main_before);
+        assert_eq!(main_first.get_uuid_string(), &String::from(second_uuid));
+        assert_eq!(main_second.get_uuid_string(), &String::from(first_uuid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_remove_player_exact_match() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+        run_migrations(&mut world)?;
+
+        let main_uuid = Uuid::now_v7();
+        let local_uuid = Uuid::now_v7();
+        let missing_uuid = Uuid::now_v7();
+        let mut party = Party::new();
+        party.player_map.insert(1, main_uuid, 1)?;
+        party.player_map.insert(2, local_uuid, 2)?;
+        world.insert_resource(party);
+        world.insert_resource(BevyEasyPlayerHandlerPlugin::init().main_player_uuid(&main_uuid).party_size(2).build());
+        world.insert_resource(PlayerIndex::default());
+        let storage = PlayerStorage::new(SqlitePlayerStore::new(world.resource::<DatabaseConnection>()));
+        world.insert_resource(storage);
+        let local_entity = world.spawn(PlayerBundle::new(None, None, Some(local_uuid), PlayerType::PlayerLocal)).id();
+
+        world.run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            let email = String::from(PLAYER_EMAIL);
+            let username = String::from(PLAYER_USERNAME);
+            phi.action_insert_player_record(&db, &main_uuid, Some(&email), Some(&username), PlayerType::PlayerMain)?;
+            phi.action_insert_player_record(&db, &local_uuid, Some(&email), Some(&username), PlayerType::PlayerLocal)
+        })?;
+
+        let remove_player = |world: &mut World, target: Uuid| {
+            world.run_system_once(move |
+                mut commands: Commands,
+                storage: Res<PlayerStorage>,
+                mut party: ResMut<Party>,
+                phi: Res<PlayerHandlerInterface>,
+                player_index: Res<PlayerIndex>,
+                mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>,
+            | {
+                phi.pipeline_db_and_party_action_remove_player(&mut commands, &storage, &mut party, &player_index, &target, &mut plugin)
+            })
+        };
+
+        // A uuid without a record fails with PlayerNotFound and leaves the party, entity and records untouched
+        match remove_player(&mut world, missing_uuid) {
+            Err(PlayerHandlerError::PlayerNotFound(player_uuid)) => assert_eq!(player_uuid, missing_uuid),
+            other => panic!("expected PlayerNotFound, got {:?}", other),
+        }
+        // The main player is refused outright, which is not a missing record
+        assert!(matches!(remove_player(&mut world, main_uuid), Err(PlayerHandlerError::Handler(_))));
+        world.flush();
+        assert!(world.get_entity(local_entity).is_some());
+        assert_eq!(world.resource::<Party>().player_map.len(), 2);
+
+        remove_player(&mut world, local_uuid)?;
+        world.flush();
+        assert!(world.get_entity(local_entity).is_none());
+        assert_eq!(world.resource::<Party>().player_map.get(&1), Some(&main_uuid));
+        assert_eq!(world.resource::<Party>().player_map.len(), 1);
+
+        let (local_exists, main_exists) = world.run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            Ok::<_, ErrorTypePlayerHandler>((phi.query_db_player_exists(&db, &local_uuid)?, phi.query_db_player_exists(&db, &main_uuid)?))
+        })?;
+        assert!(!local_exists);
+        assert!(main_exists);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_requests() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+
+        app.world_mut().send_event(AddLocalPlayerRequest { username: String::from(PLAYER_USERNAME) });
+        app.update();
+        let results: Vec<PlayerRequestResult> = app.world_mut().resource_mut::<Events<PlayerRequestResult>>().drain().collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].result.is_ok());
+
+        let party_map = app.world().resource::<Party>().get_player_map_clone()?;
+        assert_eq!(party_map.len(), 2);
+        let local_uuid = *party_map.get(&2).unwrap();
+        let db_has_local = app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_player_exists(&db, &local_uuid)
+        })?;
+        assert!(db_has_local);
+
+        // Unknown players are reported through the result event, the party is left alone. Both requests are handled in the same frame
+        app.world_mut().send_event(RemovePlayerRequest { uuid: Uuid::now_v7() });
+        app.world_mut().send_event(RemovePlayerRequest { uuid: local_uuid });
+        app.update();
+        let results: Vec<PlayerRequestResult> = app.world_mut().resource_mut::<Events<PlayerRequestResult>>().drain().collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].result.is_err());
+        assert_eq!(results[1].request, PlayerRequest::RemovePlayer(RemovePlayerRequest { uuid: local_uuid }));
+        assert!(results[1].result.is_ok());
+        assert_eq!(app.world().resource::<Party>().get_player_map_clone()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_handler_param() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local(PLAYER_USERNAME)
+        })?;
+        app.update();
+
+        let main_uuid = app.world().resource::<Party>().get_main_player_uuid()?.unwrap();
+        let (before, after, missing) = app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            let before = handler.active()?;
+            handler.set_active(&main_uuid)?;
+            let after = handler.active()?;
+            let missing = handler.set_active(&Uuid::now_v7()).is_err();
+            Ok::<_, ErrorTypePlayerHandler>((before, after, missing))
+        })?;
+        let local_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        assert_eq!(before, Some(local_uuid));
+        assert_eq!(after, Some(main_uuid));
+        assert!(missing);
+
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&local_uuid)
+        })?;
+        app.update();
+        assert_eq!(app.world().resource::<Party>().get_player_map_clone()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_test_ref_info() -> Result<(), ErrorTypePlayerHandler> {
+        let phi = PlayerHandlerInterface::get();
+
+        let plugin = BevyEasyPlayerHandlerPlugin::init().build();
+        assert_eq!(plugin.get_test_ref_player_mode()?, &TestRefPlayerMode::Disabled);
+        assert_eq!(phi.test_ref_info(&plugin)?, None);
+
+        let test_ref_uuid = Uuid::now_v7();
+        let plugin = BevyEasyPlayerHandlerPlugin::init()
+            .test_ref_player(&test_ref_uuid, PLAYER_USERNAME, PLAYER_EMAIL)
+            .build();
+        assert_eq!(
+            phi.test_ref_info(&plugin)?,
+            Some((test_ref_uuid, String::from(PLAYER_USERNAME), String::from(PLAYER_EMAIL))),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_removal_at_index_zero() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local(PLAYER_USERNAME)
+        })?;
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_ai(PLAYER_USERNAME)
+        })?;
+        app.update();
+        let local_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let ai_uuid = *app.world().resource::<Party>().player_map.get(&3).unwrap();
+
+        // Despawning with the active index at 0 used to underflow, the gap left behind is closed
+        app.world_mut().resource_mut::<Party>().active_player = 0;
+        let local_entity = app.world().resource::<PlayerIndex>().get_entity(&local_uuid).unwrap();
+        app.world_mut().despawn(local_entity);
+        app.update();
+        let party = app.world().resource::<Party>();
+        assert_eq!(party.get_active_player_index()?, 1);
+        assert_eq!(party.player_map.len(), 2);
+        assert_eq!(party.player_map.get(&2), Some(&ai_uuid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_profile() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local(PLAYER_USERNAME)
+        })?;
+        app.update();
+        let guest_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let guest_entity = app.world().resource::<PlayerIndex>().get_entity(&guest_uuid).unwrap();
+        assert_eq!(app.world().get::<PlayerProfile>(guest_entity), Some(&PlayerProfile::new()));
+
+        app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.set_profile_value(&guest_uuid, "colour", "#ff8800")?;
+            profiles.set_profile_value(&guest_uuid, "difficulty", 3)?;
+            profiles.set_profile_value(&guest_uuid, "tutorial_done", true)?;
+            profiles.set_profile_value(&guest_uuid, "volume", 0.5)
+        })?;
+        let profile = app.world().get::<PlayerProfile>(guest_entity).unwrap();
+        assert_eq!(profile.len(), 4);
+        assert_eq!(profile.get::<String>("colour"), Some("#ff8800".to_string()));
+        assert_eq!(profile.get::<bool>("difficulty"), None);
+
+        let (difficulty, tutorial_done, missing, mismatched, unknown_player) = app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            (
+                profiles.profile_value::<i64>(&guest_uuid, "difficulty"),
+                profiles.profile_value::<bool>(&guest_uuid, "tutorial_done"),
+                profiles.profile_value::<String>(&guest_uuid, "language"),
+                profiles.profile_value::<bool>(&guest_uuid, "volume").is_err(),
+                profiles.set_profile_value(&Uuid::now_v7(), "colour", "#000000").is_err(),
+            )
+        });
+        assert_eq!(difficulty?, Some(3));
+        assert_eq!(tutorial_done?, Some(true));
+        assert_eq!(missing?, None);
+        assert!(mismatched);
+        assert!(unknown_player);
+
+        // Loading the player back from the database brings the stored profile with them
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.remove_player(&mut handler.commands, &handler.player_index, &mut handler.plugin, &guest_uuid)
+        })?;
+        app.update();
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.load_from_db(&guest_uuid)
+        })?;
+        app.update();
+        let reloaded_entity = app.world().resource::<PlayerIndex>().get_entity(&guest_uuid).unwrap();
+        let profile = app.world().get::<PlayerProfile>(reloaded_entity).unwrap();
+        assert_eq!(profile.get::<f64>("volume"), Some(0.5));
+        assert_eq!(profile.get::<i64>("difficulty"), Some(3));
+
+        // Removing the player clears their profile rows
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&guest_uuid)
+        })?;
+        let stored = app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_player_profile(&db, &guest_uuid)
+        })?;
+        assert!(stored.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_stats() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .stats_flush_interval(Duration::from_secs(10))
+                .build()
+            );
+        app.update();
+        for username in ["first_local", "second_local"] {
+            app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.add_local(username)
+            })?;
+            app.update();
+        }
+        let first_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let second_uuid = *app.world().resource::<Party>().player_map.get(&3).unwrap();
+        let player_stat = |app: &mut App, player_uuid: Uuid, stat: &'static str| {
+            app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+                phi.query_db_player_stat(&db, &player_uuid, stat)
+            })
+        };
+
+        let first_entity = app.world().resource::<PlayerIndex>().get_entity(&first_uuid).unwrap();
+        {
+            let mut stats = app.world_mut().get_mut::<PlayerStats>(first_entity).unwrap();
+            stats.increment("wins", 1);
+            assert_eq!(stats.increment("wins", 1), 2);
+            stats.set("xp", 250);
+            assert!(stats.record_high("high_score.level_1", 900));
+            assert!(!stats.record_high("high_score.level_1", 500));
+        }
+
+        // Nothing is written until the interval passes
+        app.update();
+        assert_eq!(player_stat(&mut app, first_uuid, "wins")?, None);
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(11));
+        app.update();
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::ZERO);
+        assert_eq!(player_stat(&mut app, first_uuid, "wins")?, Some(2));
+        assert_eq!(player_stat(&mut app, first_uuid, "high_score.level_1")?, Some(900));
+        assert!(!app.world().get::<PlayerStats>(first_entity).unwrap().is_dirty());
+
+        // Exiting writes whatever is pending
+        let second_entity = app.world().resource::<PlayerIndex>().get_entity(&second_uuid).unwrap();
+        app.world_mut().get_mut::<PlayerStats>(second_entity).unwrap().set("wins", 5);
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        let top_players = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_top_players_by_stat(&db, "wins", 5)
+        })?;
+        assert_eq!(top_players, vec![(second_uuid, 5), (first_uuid, 2)]);
+
+        // A player leaving the party writes their stats on the way out and gets them back when loaded again
+        app.world_mut().get_mut::<PlayerStats>(first_entity).unwrap().increment("wins", 1);
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.remove_player(&mut handler.commands, &handler.player_index, &mut handler.plugin, &first_uuid)
+        })?;
+        app.update();
+        assert_eq!(player_stat(&mut app, first_uuid, "wins")?, Some(3));
+        app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.action_increment_player_stat(&db, &first_uuid, "wins", 2)
+        })?;
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.load_from_db(&first_uuid)
+        })?;
+        app.update();
+        let first_entity = app.world().resource::<PlayerIndex>().get_entity(&first_uuid).unwrap();
+        let stats = app.world().get::<PlayerStats>(first_entity).unwrap();
+        assert_eq!(stats.get("wins"), Some(5));
+        assert_eq!(stats.get("xp"), Some(250));
+
+        // Deleting a player's record drops their unsaved stats instead of writing them back without a record
+        app.world_mut().get_mut::<PlayerStats>(second_entity).unwrap().increment("wins", 1);
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&second_uuid)
+        })?;
+        app.update();
+        let second_stats = app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_player_stats(&db, &second_uuid)
+        })?;
+        assert_eq!(second_stats.iter().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_sessions() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        assert!(app.world().resource::<PlayerSession>().session_id.is_some());
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local("local_player")
+        })?;
+        app.update();
+        let main_uuid = *app.world().resource::<Party>().player_map.get(&1).unwrap();
+        let local_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let recent_sessions = |app: &mut App| {
+            app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+                phi.query_db_recent_sessions(&db, 5)
+            })
+        };
+
+        // Players who join later are added to the open session
+        let sessions = recent_sessions(&mut app)?;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].players, vec![(1, main_uuid), (2, local_uuid)]);
+        assert_eq!(sessions[0].ended_at, None);
+
+        // Exiting closes the session
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        let sessions = recent_sessions(&mut app)?;
+        assert!(sessions[0].ended_at.is_some_and(|ended_at| ended_at >= sessions[0].started_at));
+        assert!(app.world().resource::<PlayerSession>().session_id.is_none());
+
+        let recent_players = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_recent_players(&db, 5)
+        })?;
+        assert_eq!(recent_players.len(), 2);
+        assert!(recent_players.iter().all(|player| player.player_type != PlayerType::PlayerTestRef));
+        let (created_at, last_seen_at) = app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_player_timestamps(&db, &local_uuid)
+        })?.unwrap();
+        assert!(created_at > 0);
+        assert!(last_seen_at.is_some_and(|last_seen_at| last_seen_at >= created_at));
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_party_presets() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(3)
+                .build()
+            );
+        app.update();
+        let add_ai = |app: &mut App, username: &'static str| -> Result<(), ErrorTypePlayerHandler> {
+            app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.add_ai(username)
+            })?;
+            app.update();
+            Ok(())
+        };
+        let leave_party = |app: &mut App, player_uuid: Uuid| -> Result<(), ErrorTypePlayerHandler> {
+            app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.party.remove_player(&mut handler.commands, &handler.player_index, &mut handler.plugin, &player_uuid)
+            })?;
+            app.update();
+            Ok(())
+        };
+        add_ai(&mut app, "companion_a")?;
+        add_ai(&mut app, "companion_b")?;
+        let main_uuid = *app.world().resource::<Party>().player_map.get(&1).unwrap();
+        let first_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let second_uuid = *app.world().resource::<Party>().player_map.get(&3).unwrap();
+
+        app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.save_current_party_as_preset("companions")
+        })?;
+        let presets = app.world_mut().run_system_once(|presets: PlayerPresetHandler| {
+            presets.list_presets()
+        })?;
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].name, "companions");
+        assert_eq!(presets[0].members, vec![(1, main_uuid), (2, first_uuid), (3, second_uuid)]);
+
+        // Both companions leave, and the second one's record is deleted
+        leave_party(&mut app, first_uuid)?;
+        leave_party(&mut app, second_uuid)?;
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&second_uuid)
+        })?;
+        app.update();
+        assert_eq!(app.world().resource::<Party>().player_map.len(), 1);
+
+        let report = app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.load_preset("companions")
+        })?;
+        assert_eq!(report.already_seated, vec![main_uuid]);
+        assert_eq!(report.queued, vec![first_uuid]);
+        assert_eq!(report.missing, vec![second_uuid]);
+        app.update();
+        app.update();
+        assert!(app.world().resource::<Party>().player_map.contains_uuid(&first_uuid));
+        assert_eq!(app.world().resource::<Party>().player_map.len(), 2);
+
+        // Nothing is queued when the preset would overflow the party
+        leave_party(&mut app, first_uuid)?;
+        add_ai(&mut app, "companion_c")?;
+        add_ai(&mut app, "companion_d")?;
+        let overflow = app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.load_preset("companions").is_err()
+        });
+        assert!(overflow);
+        assert!(app.world().resource::<PresetLoadQueue>().pending.is_empty());
+
+        app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.delete_preset("companions")
+        })?;
+        let deleted_again = app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.delete_preset("companions").is_err()
+        });
+        assert!(deleted_again);
+        let presets = app.world_mut().run_system_once(|presets: PlayerPresetHandler| {
+            presets.list_presets()
+        })?;
+        assert!(presets.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_profile_selection() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .party_size(4)
+                .profile_selection()
+                .build()
+            );
+        app.update();
+        let parent_uuid = app.world_mut().run_system_once(|mut profiles: PlayerProfileHandler| {
+            profiles.create_profile("parent@example.com", "parent")
+        })?;
+        let kid_uuid = app.world_mut().run_system_once(|mut profiles: PlayerProfileHandler| {
+            profiles.create_profile("kid@example.com", "kid")
+        })?;
+        let profiles = app.world_mut().run_system_once(|profiles: PlayerProfileHandler| {
+            profiles.list_local_profiles()
+        })?;
+        let profile_names: Vec<&str> = profiles.iter().map(|profile| profile.get_username_string().as_str()).collect();
+        assert_eq!(profile_names, vec!["parent", "kid"]);
+
+        // Startup waits for a profile
+        app.update();
+        assert_eq!(*app.world().resource::<State<ProfileSelection>>().get(), ProfileSelection::Selecting);
+        assert_eq!(app.world().resource::<Party>().player_map.len(), 0);
+        assert!(app.world().resource::<PlayerSession>().session_id.is_none());
+
+        app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.select_profile(&kid_uuid)
+        })?;
+        let selected_twice = app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.select_profile(&parent_uuid).is_err()
+        });
+        assert!(selected_twice);
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<ProfileSelection>>().get(), ProfileSelection::Selected);
+        assert_eq!(app.world().resource::<Party>().player_map.get(&1), Some(&kid_uuid));
+        assert_eq!(app.world().resource::<Party>().main_player_uuid, Some(kid_uuid));
+        assert!(app.world().resource::<PlayerSession>().session_id.is_some());
+        let main_player = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_main_player(&db)
+        })?;
+        assert_eq!(main_player.get_uuid_string(), &kid_uuid.to_string());
+
+        // The selected profile stays, the other one can be deleted
+        let deleted_selected = app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.delete_profile(&kid_uuid).is_err()
+        });
+        assert!(deleted_selected);
+        app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.delete_profile(&parent_uuid)
+        })?;
+        let profiles = app.world_mut().run_system_once(|profiles: PlayerProfileHandler| {
+            profiles.list_local_profiles()
+        })?;
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].get_uuid_string(), &kid_uuid.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_handler_state() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .build()
+            );
+        // Game systems ordered after the roster see the main player seated on the first frame
+        app.add_systems(Update, (|party: Res<Party>, mut seated: Local<bool>| {
+            if !*seated {
+                assert_eq!(party.player_map.len(), 1);
+                *seated = true;
+            }
+        }).after(PlayerHandlerSet::Roster));
+        app.update();
+        assert_eq!(*app.world().resource::<State<PlayerHandlerState>>().get(), PlayerHandlerState::Initializing);
+        app.update();
+        assert_eq!(*app.world().resource::<State<PlayerHandlerState>>().get(), PlayerHandlerState::Ready);
+        assert!(app.world().resource::<PlayerHandlerStartupError>().error.is_none());
+
+        // A database from a newer build fails startup instead of panicking
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"));
+        {
+            let db = app.world().resource::<DatabaseConnection>();
+            let conn = db.get_connection();
+            let conn = conn.lock().unwrap();
+            conn.execute_batch(&format!("PRAGMA user_version = {}", PLAYER_TABLE_SCHEMA_VERSION + 1)).unwrap();
+        }
+        app.add_plugins(BevyEasyPlayerHandlerPlugin::init()
+            .main_player_email(PLAYER_EMAIL)
+            .main_player_username(PLAYER_USERNAME)
+            .build()
+        );
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<PlayerHandlerState>>().get(), PlayerHandlerState::Failed);
+        assert!(matches!(
+            app.world().resource::<PlayerHandlerStartupError>().error,
+            Some(PlayerHandlerError::SchemaTooNew { .. }),
+        ));
+        assert_eq!(app.world().resource::<Party>().player_map.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_db_tx() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = world_with_memory_db();
+        run_migrations(&mut world)?;
+
+        let main_uuid = Uuid::now_v7();
+        world.run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            let email = String::from(PLAYER_EMAIL);
+            let username = String::from(PLAYER_USERNAME);
+
+            // A failed step rolls back everything done before it in the same transaction
+            let failed = phi.with_db_tx(&db, |tx| {
+                tx.action_insert_player_record(&main_uuid, Some(&email), Some(&username), PlayerType::PlayerMain)?;
+                tx.action_set_main_player_record(&Uuid::now_v7())
+            });
+            assert!(failed.is_err());
+            assert!(!phi.query_db_player_exists(&db, &main_uuid)?);
+
+            let count = phi.with_db_tx(&db, |tx| {
+                tx.action_insert_player_record(&main_uuid, Some(&email), Some(&username), PlayerType::PlayerMain)?;
+                tx.action_set_main_player_record(&main_uuid)?;
+                assert!(tx.query_db_main_player_exists()?);
+                tx.action_count_players_in_db()
+            })?;
+            assert_eq!(count, 1);
+            assert_eq!(phi.query_db_main_player(&db)?.get_uuid_string(), &String::from(main_uuid));
+
+            // A panic rolls back too, and the connection is not left poisoned for the next caller
+            let panicked_uuid = Uuid::now_v7();
+            let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                phi.with_db_tx::<()>(&db, |tx| {
+                    tx.action_insert_player_record(&panicked_uuid, Some(&email), Some(&username), PlayerType::PlayerLocal)?;
+                    panic!("failure inside the transaction");
+                })
+            }));
+            assert!(panicked.is_err());
+            assert!(!phi.query_db_player_exists(&db, &panicked_uuid)?);
+            assert_eq!(phi.query_db_count_existing_players(&db)?, 1);
+            Ok::<_, ErrorTypePlayerHandler>(())
+        })?;
+
+        // Resetting the roster swaps every record for a new main player in one transaction
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local("local_player")
+        })?;
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.reset()
+        })?;
+        app.update();
+        let players = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_existing_players(&db)
+        })?;
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].get_player_type(), &PlayerType::PlayerMain);
+        assert_eq!(app.world().resource::<Party>().player_map.get(&1).map(Uuid::to_string), Some(players[0].get_uuid_string().clone()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_player_store() -> Result<(), ErrorTypePlayerHandler> {
+        let main_uuid = Uuid::now_v7();
+        let local_uuid = Uuid::now_v7();
+        let mut store = MemoryPlayerStore::new();
+        for (player_uuid, player_type) in [(main_uuid, PlayerType::PlayerMain), (local_uuid, PlayerType::PlayerLocal)] {
+            store.insert_player(&DBPlayer {
+                uuid: player_uuid.to_string(),
+                email: String::from(PLAYER_EMAIL),
+                username: String::from(PLAYER_USERNAME),
+                player_type,
+            })?;
+        }
+        assert!(store.set_main_player(&Uuid::now_v7()).is_err());
+        store.set_main_player(&main_uuid)?;
+        assert_eq!(store.main_player()?.map(|player| player.uuid), Some(main_uuid.to_string()));
+        store.remove_player(&local_uuid)?;
+        assert!(store.remove_player(&local_uuid).is_err());
+        assert_eq!(store.list_players()?.len(), 1);
+
+        // The in-memory backend keeps the records itself, no DatabaseConnection is needed or made
+        let mut app = App::new();
+        app.add_plugins(BevyEasyPlayerHandlerPlugin::init()
+            .main_player_email(PLAYER_EMAIL)
+            .main_player_username(PLAYER_USERNAME)
+            .party_size(4)
+            .player_store(PlayerStoreBackend::Memory)
+            .build()
+        );
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<State<PlayerHandlerState>>().get(), &PlayerHandlerState::Ready);
+        assert!(!app.world().contains_resource::<DatabaseConnection>());
+        let main_player = app.world().resource::<PlayerStorage>().main_player()?;
+        let plugin_main_uuid = app.world().resource::<BevyEasyPlayerHandlerPlugin>().get_main_player_uuid()?.copied();
+        assert_eq!(main_player.map(|player| player.uuid), plugin_main_uuid.map(|player_uuid| player_uuid.to_string()));
+
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local("local_player")
+        })?;
+        app.update();
+        let players = app.world().resource::<PlayerStorage>().list_players()?;
+        assert_eq!(players.len(), 2);
+        let local_uuid = players
+            .iter()
+            .find(|player| player.get_player_type() == &PlayerType::PlayerLocal)
+            .map(|player| Uuid::parse_str(player.get_uuid_string()))
+            .expect("the local player was not stored")
+            .map_err(|e| ErrorTypePlayerHandler::UuidParsingFailed(e.to_string()))?;
+
+        // Profiles and presets are kept by the same backend
+        let difficulty = app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.set_profile_value(&local_uuid, "difficulty", 3)?;
+            profiles.profile_value::<i64>(&local_uuid, "difficulty")
+        })?;
+        let presets = app.world_mut().run_system_once(|mut presets: PlayerPresetHandler| {
+            presets.save_current_party_as_preset("companions")?;
+            presets.list_presets()
+        })?;
+        assert_eq!(difficulty, Some(3));
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].members.len(), 2);
+
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&local_uuid)
+        }).map_err(ErrorTypePlayerHandler::from)?;
+        app.update();
+        assert!(!app.world().resource::<PlayerStorage>().player_exists(&local_uuid)?);
+        assert_eq!(app.world().resource::<PlayerStorage>().list_players()?.len(), 1);
+        assert!(app.world().resource::<PlayerStorage>().player_profile(&local_uuid)?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "file_store")]
+    #[test]
+    fn test_database_file_player_store() -> Result<(), ErrorTypePlayerHandler> {
+        let path = std::env::temp_dir().join(format!("player_store_{}.ron", Uuid::now_v7()));
+        // A sibling that only differs by extension is not the save's temp file
+        let sibling_path = path.with_extension("tmp");
+        std::fs::write(&sibling_path, "keep")
+            .map_err(|e| ErrorTypePlayerHandler::DBActionFailed(e.to_string()))?;
+        let app_with_file_store = || {
+            let mut app = App::new();
+            app.add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .player_store(PlayerStoreBackend::File { path: path.clone(), format: PlayerStoreFormat::Ron })
+                .build()
+            );
+            app.update();
+            app.update();
+            app
+        };
+
+        let app = app_with_file_store();
+        let first_main_uuid = app.world().resource::<BevyEasyPlayerHandlerPlugin>().get_main_player_uuid()?.copied();
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| ErrorTypePlayerHandler::DBQueryFailed(e.to_string()))?;
+        assert!(contents.contains(PLAYER_USERNAME));
+        drop(app);
+
+        // A second run loads the stored main player instead of making a new one
+        let app = app_with_file_store();
+        assert_eq!(app.world().resource::<BevyEasyPlayerHandlerPlugin>().get_main_player_uuid()?.copied(), first_main_uuid);
+        assert_eq!(app.world().resource::<PlayerStorage>().list_players()?.len(), 1);
+        assert_eq!(std::fs::read_to_string(&sibling_path).ok().as_deref(), Some("keep"));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&sibling_path);
+        Ok(())
+    }
+
+    #[test]
+    fn test_database_write_behind() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .write_behind()
+                .build()
+            );
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local("local_player")
+        })?;
+        app.update();
+        let local_uuid = app.world().resource::<Party>().player_map.get(&2).copied();
+        let Some(local_uuid) = local_uuid else {
+            panic!("local player was not seated");
+        };
+
+        // The insert is queued on the worker, flush waits until it is committed
+        app.world().resource::<PlayerDbWriter>().flush()?;
+        assert_eq!(app.world().resource::<PlayerDbWriter>().pending(), 0);
+        let local_exists = app.world_mut().run_system_once(move |db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_player_exists(&db, &local_uuid)
+        })?;
+        assert!(local_exists);
+
+        // A duplicate fails alone, the write queued with it is still committed
+        let ai_uuid = Uuid::now_v7();
+        let db_writer = app.world().resource::<PlayerDbWriter>();
+        for player_uuid in [local_uuid, ai_uuid] {
+            db_writer.queue(PlayerDbWrite::InsertPlayer {
+                player_uuid,
+                email: Some(String::from(PLAYER_EMAIL)),
+                username: Some(String::from("ai_player")),
+                player_type: PlayerType::PlayerAiLocal,
+            })?;
+        }
+        db_writer.flush()?;
+        app.update();
+        let write_results = app.world().resource::<Events<PlayerDbWriteResult>>();
+        let mut reader = write_results.get_reader();
+        let failed: Vec<bool> = reader.read(write_results).map(|write_result| write_result.result.is_err()).collect();
+        assert_eq!(failed, vec![false, true, false]);
+
+        // Removing right after queueing still sees the record, the pipeline flushes first
+        app.world().resource::<PlayerDbWriter>().queue(PlayerDbWrite::InsertPlayer {
+            player_uuid: Uuid::now_v7(),
+            email: Some(String::from(PLAYER_EMAIL)),
+            username: Some(String::from("queued_player")),
+            player_type: PlayerType::PlayerLocal,
+        })?;
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&local_uuid)
+        })?;
+        app.update();
+        let players = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_existing_players(&db)
+        })?;
+        assert_eq!(players.len(), 3);
+
+        // A profile write lands after the insert queued ahead of it
+        let queued_uuid = Uuid::now_v7();
+        app.world().resource::<PlayerDbWriter>().queue(PlayerDbWrite::InsertPlayer {
+            player_uuid: queued_uuid,
+            email: Some(String::from(PLAYER_EMAIL)),
+            username: Some(String::from("profile_player")),
+            player_type: PlayerType::PlayerLocal,
+        })?;
+        let difficulty = app.world_mut().run_system_once(move |mut profiles: PlayerProfileHandler| {
+            profiles.set_profile_value(&queued_uuid, "difficulty", 2)?;
+            profiles.profile_value::<i64>(&queued_uuid, "difficulty")
+        })?;
+        assert_eq!(difficulty, Some(2));
         Ok(())
     }
 }
\ No newline at end of file
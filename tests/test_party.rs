@@ -1,10 +1,18 @@
 #[cfg(test)]
 mod tests {
-    use bevy::prelude::*;
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::*,
+    };
     use bevy_easy_player_handler::*;
-    use bevy_easy_shared_definitions::ErrorTypePlayerHandler;
+    use bevy_easy_player_handler::handlers::input_binding::release_input_bindings;
+    use bevy_easy_shared_definitions::{
+        DatabaseConnection,
+        ErrorTypePlayerHandler,
+    };
     use std::{collections::HashMap,
         sync::{Arc, Mutex},
+        time::Duration,
     };
     use uuid::Uuid;
 
@@ -45,7 +53,7 @@ mod tests {
         let mut reference_map: HashMap<usize, Uuid> = HashMap::new();
         reference_map.insert(1, player_uuid.clone());
 
-        party.player_map.insert(1, player_uuid.clone());
+        party.player_map.insert(1, player_uuid, 1)?;
         let clone: HashMap<usize, Uuid> = party.get_player_map_clone()?;
         
         assert_eq!(reference_map, clone);
@@ -62,7 +70,7 @@ mod tests {
         let uuid: Option<Uuid> = Some(Uuid::now_v7());
         let uuid_unwrapped: Uuid = uuid.unwrap();
 
-        party.player_map.insert(party_size_plus_one, uuid_unwrapped);
+        party.player_map.insert(party_size_plus_one, uuid_unwrapped, party_size_plus_one)?;
         
         let active_player_id = party.get_player_map_active_player_uuid()?;
         let active_player_id_unwrapped = active_player_id.unwrap().clone();
@@ -215,7 +223,7 @@ mod tests {
         let mut party: Party = Party::new(); 
         let old_uuid = Uuid::new_v4();
         let new_uuid = Uuid::now_v7();
-        party.player_map.insert(1, old_uuid);
+        party.player_map.insert(1, old_uuid, 1)?;
         party.set_active_player_uuid_player_map(&new_uuid)?;
         assert_eq!(party.player_map.get(&1), Some(&new_uuid));
         Ok(())
@@ -305,7 +313,7 @@ mod tests {
         let mut party: Party = Party::new(); 
         let old_uuid = Uuid::new_v4();
         let new_uuid = Uuid::now_v7();
-        party.player_map.insert(1, old_uuid.clone());
+        party.player_map.insert(1, old_uuid, 1)?;
         let reference_exists: bool = party.verify_player_exists_player_map_uuid(&old_uuid)?;
         let reference_missing: bool = party.verify_player_exists_player_map_uuid(&new_uuid)?;
         assert_eq!(reference_exists, true);
@@ -317,7 +325,7 @@ mod tests {
     fn test_party_verify_player_exists_player_map_index() -> Result<(), ErrorTypePlayerHandler> {
         let mut party: Party = Party::new(); 
         let player_uuid = Uuid::new_v4();
-        party.player_map.insert(1, player_uuid);
+        party.player_map.insert(1, player_uuid, 1)?;
         let reference_exists: bool = party.verify_player_exists_player_map_index(1)?;
         let reference_missing: bool = party.verify_player_exists_player_map_index(2)?;
         assert_eq!(reference_exists, true);
@@ -381,7 +389,7 @@ mod tests {
         todo!("Plugin Logic");
         let mut party: Party = Party::new(); 
         let player_uuid = Uuid::new_v4();
-        party.player_map.insert(1, player_uuid);
+        party.player_map.insert(1, player_uuid, 1)?;
         let reference_exists: bool = party.verify_player_exists_player_map_index(1)?;
         // party.player_map_remove_player(plugin, target_player);
         let reference_missing: bool = party.verify_player_exists_player_map_index(1)?;
@@ -480,8 +488,8 @@ mod tests {
         let mut party: Party = Party::new(); 
         let original_player_1_uuid: Uuid = Uuid::new_v4();
         let original_player_2_uuid: Uuid = Uuid::now_v7();
-        party.player_map.insert(1, original_player_1_uuid.clone());
-        party.player_map.insert(2, original_player_2_uuid.clone());
+        party.player_map.insert(1, original_player_1_uuid, 1)?;
+        party.player_map.insert(2, original_player_2_uuid, 2)?;
 
         // Swap the players info and verify it maps correctly.
         party.player_map_swap_players(1, 2)?;
@@ -497,4 +505,640 @@ mod tests {
         assert_eq!(updated_player_2_uuid, &original_player_1_uuid);
         Ok(())
     }
+    #[test]
+    fn test_party_events_queued() -> Result<(), ErrorTypePlayerHandler> {
+        let mut party: Party = Party::new(); 
+        let player_1_uuid: Uuid = Uuid::now_v7();
+        let player_2_uuid: Uuid = Uuid::now_v7();
+        party.player_map.insert(1, player_1_uuid, 1)?;
+        party.player_map.insert(2, player_2_uuid, 2)?;
+
+        // Setting the index already active is not a change
+        party.set_active_player_index(1)?;
+        assert!(party.drain_pending_events().is_empty());
+
+        party.player_map_swap_players(1, 2)?;
+        party.set_main_player_uuid(&player_2_uuid)?;
+        let events = party.drain_pending_events();
+
+        let mut reordered_map: PartySlots = PartySlots::new();
+        reordered_map.insert(1, player_2_uuid, 1)?;
+        reordered_map.insert(2, player_1_uuid, 2)?;
+        assert_eq!(events, vec![
+            PartyEvent::PartyReordered(PartyReordered { player_map: reordered_map }),
+            PartyEvent::ActivePlayerChanged(ActivePlayerChanged { previous_index: 1, active_index: 2, player_uuid: Some(player_1_uuid) }),
+            PartyEvent::MainPlayerChanged(MainPlayerChanged { previous_uuid: None, main_player_uuid: player_2_uuid }),
+        ]);
+        assert!(party.pending_events.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_events_sent() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.add_event::<PlayerLeftParty>()
+            .add_event::<PlayerJoinedParty>()
+            .add_event::<ActivePlayerChanged>()
+            .add_event::<MainPlayerChanged>()
+            .add_event::<PlayerRecordInserted>()
+            .add_event::<PlayerRecordDeleted>()
+            .add_event::<PartyReordered>()
+            .insert_resource(BevyEasyPlayerHandlerPlugin::init().party_size(2).build())
+            .insert_resource(Party::new())
+            .add_systems(Update, send_party_events);
+
+        let player_1_uuid: Uuid = Uuid::now_v7();
+        let player_2_uuid: Uuid = Uuid::now_v7();
+        app.world_mut().resource_mut::<Party>().player_map.insert(1, player_1_uuid, 1)?;
+        app.world_mut().resource_mut::<Party>().player_map.insert(2, player_2_uuid, 2)?;
+        app.world_mut().run_system_once(move |mut party: ResMut<Party>, mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>| {
+            party.player_map_remove_player(&mut plugin, &player_1_uuid)
+        })?;
+        app.update();
+
+        let left: Vec<PlayerLeftParty> = app.world_mut().resource_mut::<Events<PlayerLeftParty>>().drain().collect();
+        let reordered: Vec<PartyReordered> = app.world_mut().resource_mut::<Events<PartyReordered>>().drain().collect();
+        assert_eq!(left, vec![PlayerLeftParty { player_uuid: player_1_uuid, party_index: 1 }]);
+        assert_eq!(reordered.len(), 1);
+        assert_eq!(reordered[0].player_map.get(&1), Some(&player_2_uuid));
+        assert!(app.world().resource::<Party>().pending_events.is_empty());
+        Ok(())
+    }
+    #[test]
+    fn test_party_player_data_components() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = World::new();
+        world.insert_resource(PlayerIndex::default());
+        let main_uuid: Uuid = Uuid::now_v7();
+        let ai_uuid: Uuid = Uuid::now_v7();
+        world.spawn(PlayerBundle::new(Some(PLAYER_EMAIL.to_string()), Some(PLAYER_USERNAME.to_string()), Some(main_uuid), PlayerType::PlayerMain));
+        world.spawn(PlayerBundle::new(None, Some(ALT_PLAYER_USERNAME.to_string()), Some(ai_uuid), PlayerType::PlayerAiLocal));
+        let mut party: Party = Party::new();
+        party.player_map.insert(1, main_uuid, 1)?;
+        party.player_map.insert(2, ai_uuid, 2)?;
+        world.insert_resource(party);
+
+        world.run_system_once(move |mut commands: Commands, mut party: ResMut<Party>, player_index: Res<PlayerIndex>, player_query: Query<PlayerData>| {
+            assert_eq!(party.get_player_count_party(&player_query)?, 2);
+            assert_eq!(party.get_player_count_ai_local(&player_query)?, 1);
+            assert_eq!(party.clone_main_player_uuid(&player_query)?, main_uuid);
+            assert_eq!(party.get_party_local_ai_uuids_vec(&player_query)?, vec![ai_uuid]);
+
+            party.set_active_player_index(2)?;
+            assert_eq!(party.clone_active_player_uuid(&player_index, &player_query)?, ai_uuid);
+            assert_eq!(party.clone_active_player_player_type(&player_index, &player_query)?, PlayerType::PlayerAiLocal);
+            assert!(party.clone_active_player_player_email(&player_index, &player_query).is_err());
+            assert_eq!(party.verify_player_exists_player_map_and_component(&player_index, &ai_uuid)?, (true, true));
+            party.set_active_player_username(&mut commands, &player_index, &player_query, PLAYER_USERNAME)
+        })?;
+
+        // Component writes go through Commands and land once they are applied
+        let mut names = world.query::<(&PlayerId, &PlayerName)>();
+        for (player_id, player_name) in names.iter(&world) {
+            assert_eq!(player_name.0.as_deref(), Some(PLAYER_USERNAME), "player [{}]", player_id.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_player_index_tracks_player_id() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = World::new();
+        world.insert_resource(PlayerIndex::default());
+        let player_uuid: Uuid = Uuid::now_v7();
+        let new_uuid: Uuid = Uuid::now_v7();
+        let entity = world.spawn(PlayerBundle::new(None, Some(PLAYER_USERNAME.to_string()), Some(player_uuid), PlayerType::PlayerLocal)).id();
+        assert_eq!(world.resource::<PlayerIndex>().get_entity(&player_uuid), Some(entity));
+        assert_eq!(world.resource::<PlayerIndex>().get_uuid(entity), Some(player_uuid));
+
+        // Replacing the PlayerId moves the entry to the new uuid
+        world.entity_mut(entity).insert(PlayerId(new_uuid));
+        assert!(!world.resource::<PlayerIndex>().contains(&player_uuid));
+        assert_eq!(world.resource::<PlayerIndex>().get_entity(&new_uuid), Some(entity));
+        assert_eq!(world.resource::<PlayerIndex>().len(), 1);
+
+        world.despawn(entity);
+        assert!(world.resource::<PlayerIndex>().is_empty());
+        assert_eq!(world.resource::<PlayerIndex>().get_uuid(entity), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_slots() -> Result<(), ErrorTypePlayerHandler> {
+        let mut slots: PartySlots = PartySlots::new();
+        let player_1_uuid: Uuid = Uuid::now_v7();
+        let player_2_uuid: Uuid = Uuid::now_v7();
+        let player_3_uuid: Uuid = Uuid::now_v7();
+        assert_eq!(slots.push(player_1_uuid), 1);
+        assert_eq!(slots.push(player_3_uuid), 2);
+        slots.insert_at(2, player_2_uuid)?;
+        assert_eq!(slots.iter().map(|(_, uuid)| *uuid).collect::<Vec<Uuid>>(), vec![player_1_uuid, player_2_uuid, player_3_uuid]);
+
+        slots.move_seat(3, 1)?;
+        assert_eq!(slots.seat_of(&player_3_uuid), Some(1));
+        assert_eq!(slots.seat_of(&player_1_uuid), Some(2));
+        slots.swap(1, 3)?;
+        assert_eq!(slots.get(&1), Some(&player_2_uuid));
+        assert_eq!(slots.get(&3), Some(&player_3_uuid));
+
+        // Removal compacts, taking leaves the seat open until compact
+        assert_eq!(slots.remove(&1), Some(player_2_uuid));
+        assert_eq!(slots.get(&1), Some(&player_1_uuid));
+        assert_eq!(slots.take(&1), Some(player_1_uuid));
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots.seat_count(), 2);
+        assert_eq!(slots.first_open_seat(), 1);
+        assert!(slots.compact());
+        assert_eq!(slots.get(&1), Some(&player_3_uuid));
+
+        // Seat 0 and seats past the end are rejected without panicking
+        assert_eq!(slots.get(&0), None);
+        assert!(slots.insert(0, player_1_uuid, 4).is_err());
+        assert!(slots.insert(usize::MAX, player_1_uuid, 4).is_err());
+        assert!(slots.insert(2, player_3_uuid, 4).is_err());
+        assert_eq!(slots.remove(&0), None);
+        assert_eq!(slots.take(&9), None);
+        assert!(slots.insert_at(0, player_1_uuid).is_err());
+        assert!(slots.insert_at(3, player_1_uuid).is_err());
+        assert!(slots.move_seat(0, 1).is_err());
+        assert!(slots.move_seat(1, 2).is_err());
+        assert!(slots.swap(1, 2).is_err());
+        assert_eq!(slots.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_player_map_move_and_insert_player_at() -> Result<(), ErrorTypePlayerHandler> {
+        let mut party: Party = Party::new();
+        let player_1_uuid: Uuid = Uuid::now_v7();
+        let player_2_uuid: Uuid = Uuid::now_v7();
+        let player_3_uuid: Uuid = Uuid::now_v7();
+        party.player_map.insert(1, player_1_uuid, 1)?;
+        party.player_map.insert(2, player_2_uuid, 2)?;
+
+        party.player_map_insert_player_at(1, &player_3_uuid)?;
+        assert!(party.player_map_insert_player_at(1, &player_3_uuid).is_err());
+        party.player_map_move_player(1, 3)?;
+        assert_eq!(party.get_player_map_active_player_uuid()?, Some(player_3_uuid));
+        assert_eq!(party.player_map.get(&1), Some(&player_1_uuid));
+        assert_eq!(party.player_map.get(&2), Some(&player_2_uuid));
+        assert!(party.player_map_move_player(3, 4).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_fixed_seats_remove_player() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = World::new();
+        world.insert_resource(BevyEasyPlayerHandlerPlugin::init().party_size(3).seating_mode(PartySeatingMode::FixedSeats).build());
+        world.insert_resource(PlayerIndex::default());
+        let player_1_uuid: Uuid = Uuid::now_v7();
+        let player_2_uuid: Uuid = Uuid::now_v7();
+        let player_3_uuid: Uuid = Uuid::now_v7();
+        let mut party: Party = Party::new();
+        party.player_map.insert(1, player_1_uuid, 1)?;
+        party.player_map.insert(2, player_2_uuid, 2)?;
+        party.player_map.insert(3, player_3_uuid, 3)?;
+        world.insert_resource(party);
+        let player_3_entity = world.spawn(PlayerBundle::new(None, None, Some(player_3_uuid), PlayerType::PlayerLocal)).id();
+
+        world.run_system_once(move |mut party: ResMut<Party>, mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>| {
+            party.player_map_remove_player(&mut plugin, &player_2_uuid)
+        })?;
+        world.run_system_once(sync_party_seats);
+        let party = world.resource::<Party>();
+        assert_eq!(party.player_map.get(&2), None);
+        assert_eq!(party.player_map.get(&3), Some(&player_3_uuid));
+        assert_eq!(party.player_map.first_open_seat(), 2);
+        assert!(!party.pending_events.iter().any(|event| matches!(event, PartyEvent::PartyReordered(_))));
+        assert_eq!(world.get::<PartySeat>(player_3_entity), Some(&PartySeat(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_compact_remove_player_keeps_active_seat() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = World::new();
+        world.insert_resource(BevyEasyPlayerHandlerPlugin::init().party_size(4).build());
+        let player_uuids = [Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7()];
+        let mut party: Party = Party::new();
+        for (n, player_uuid) in player_uuids.iter().enumerate() {
+            party.player_map.insert(n + 1, *player_uuid, n + 1)?;
+        }
+        party.set_active_player_index(2)?;
+        world.insert_resource(party);
+        let remove_player = |world: &mut World, target: Uuid| {
+            world.run_system_once(move |mut party: ResMut<Party>, mut plugin: ResMut<BevyEasyPlayerHandlerPlugin>| {
+                party.player_map_remove_player(&mut plugin, &target)
+            })
+        };
+
+        // A seat behind the active one leaves, the active player keeps their seat
+        remove_player(&mut world, player_uuids[3])?;
+        assert_eq!(world.resource::<Party>().get_active_player_index()?, 2);
+        assert_eq!(world.resource::<Party>().get_player_map_active_player_uuid()?, Some(player_uuids[1]));
+
+        // A seat ahead of it leaves, the active player moves up with everyone behind the gap
+        remove_player(&mut world, player_uuids[0])?;
+        assert_eq!(world.resource::<Party>().get_active_player_index()?, 1);
+        assert_eq!(world.resource::<Party>().get_player_map_active_player_uuid()?, Some(player_uuids[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_release_input_bindings() -> Result<(), ErrorTypePlayerHandler> {
+        let mut world = World::new();
+        world.insert_resource(PlayerIndex::default());
+        let seated_uuid: Uuid = Uuid::now_v7();
+        let left_uuid: Uuid = Uuid::now_v7();
+        let mut party: Party = Party::new();
+        party.player_map.insert(1, seated_uuid, 1)?;
+        world.insert_resource(party);
+        let seated_entity = world.spawn((PlayerBundle::new(None, None, Some(seated_uuid), PlayerType::PlayerLocal), PlayerInputBinding::Keyboard(KeyboardLayout::Full))).id();
+        let left_entity = world.spawn((PlayerBundle::new(None, None, Some(left_uuid), PlayerType::PlayerLocal), PlayerInputBinding::Gamepad(Gamepad::new(0)))).id();
+
+        world.run_system_once(release_input_bindings);
+        assert_eq!(world.get::<PlayerInputBinding>(seated_entity), Some(&PlayerInputBinding::Keyboard(KeyboardLayout::Full)));
+        assert_eq!(world.get::<PlayerInputBinding>(left_entity), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_fixed_seats() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .seating_mode(PartySeatingMode::FixedSeats)
+                .build()
+            );
+        app.update();
+        for _ in 0..2 {
+            app.world_mut().send_event(AddLocalPlayerRequest { username: String::from(PLAYER_USERNAME) });
+            app.update();
+        }
+        let seat_2_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        let seat_3_uuid = *app.world().resource::<Party>().player_map.get(&3).unwrap();
+        let seat_of = |app: &App, player_uuid: &Uuid| {
+            let entity = app.world().resource::<PlayerIndex>().get_entity(player_uuid).unwrap();
+            app.world().get::<PartySeat>(entity).copied()
+        };
+        assert_eq!(seat_of(&app, &seat_3_uuid), Some(PartySeat(3)));
+
+        // Seat 2 leaving keeps seat 3 where it is
+        app.world_mut().send_event(RemovePlayerRequest { uuid: seat_2_uuid });
+        app.update();
+        assert_eq!(app.world().resource::<Party>().player_map.get(&2), None);
+        assert_eq!(seat_of(&app, &seat_3_uuid), Some(PartySeat(3)));
+
+        // The next player fills the lowest free seat
+        app.world_mut().send_event(AddLocalPlayerRequest { username: String::from(PLAYER_USERNAME) });
+        app.update();
+        let party = app.world().resource::<Party>();
+        let new_uuid = *party.player_map.get(&2).unwrap();
+        assert_ne!(new_uuid, seat_2_uuid);
+        assert_eq!(party.get_active_player_index()?, 2);
+        assert_eq!(party.player_map.get(&3), Some(&seat_3_uuid));
+        assert_eq!(seat_of(&app, &new_uuid), Some(PartySeat(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_input_binding() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(3)
+                .gamepad_join(GamepadButtonType::Start)
+                .build()
+            );
+        app.update();
+        app.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.add_local(PLAYER_USERNAME)
+        })?;
+        app.update();
+        let main_uuid = app.world().resource::<Party>().get_main_player_uuid()?.unwrap();
+        let local_uuid = *app.world().resource::<Party>().player_map.get(&2).unwrap();
+        app.world_mut().run_system_once(move |mut input: PlayerInputHandler| {
+            input.bind_keyboard(&local_uuid, KeyboardLayout::Wasd)?;
+            input.bind_keyboard(&main_uuid, KeyboardLayout::Arrows)
+        })?;
+        let taken = app.world_mut().run_system_once(move |mut input: PlayerInputHandler| {
+            input.bind_keyboard(&main_uuid, KeyboardLayout::Wasd).is_err()
+        });
+        assert!(taken);
+
+        // An unbound gamepad pressing start joins, pressing again does not add a second player
+        let gamepad = Gamepad::new(0);
+        app.world_mut().resource_mut::<ButtonInput<GamepadButton>>().press(GamepadButton::new(gamepad, GamepadButtonType::Start));
+        app.update();
+        app.update();
+        let party = app.world().resource::<Party>();
+        assert_eq!(party.player_map.len(), 3);
+        let joined_uuid = *party.player_map.get(&3).unwrap();
+        let joined_entity = app.world().resource::<PlayerIndex>().get_entity(&joined_uuid).unwrap();
+        assert_eq!(app.world().get::<PlayerInputBinding>(joined_entity), Some(&PlayerInputBinding::Gamepad(gamepad)));
+
+        // A full party ignores further gamepads
+        app.world_mut().resource_mut::<ButtonInput<GamepadButton>>().press(GamepadButton::new(Gamepad::new(1), GamepadButtonType::Start));
+        app.update();
+        assert_eq!(app.world().resource::<Party>().player_map.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_remote_players() -> Result<(), ErrorTypePlayerHandler> {
+        let loopback = LoopbackTransport::new();
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .insert_resource(RemotePlayerTransport::new(loopback.clone()))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .remote_grace_period(Duration::from_secs(5))
+                .build()
+            );
+        app.update();
+
+        let remote_uuid = Uuid::now_v7();
+        loopback.send(RemotePeerEvent::Connecting { player_uuid: remote_uuid, username: Some(PLAYER_USERNAME.to_string()), player_type: PlayerType::PlayerRemote });
+        app.update();
+        let remote_entity = app.world().resource::<PlayerIndex>().get_entity(&remote_uuid).unwrap();
+        assert_eq!(app.world().resource::<Party>().player_map.seat_of(&remote_uuid), Some(2));
+        assert_eq!(app.world().get::<RemoteConnectionState>(remote_entity), Some(&RemoteConnectionState::Connecting));
+        assert!(app.world().resource::<PlayerStorage>().player_exists(&remote_uuid)?);
+
+        loopback.send(RemotePeerEvent::Connected { player_uuid: remote_uuid });
+        app.update();
+        assert_eq!(app.world().get::<RemoteConnectionState>(remote_entity), Some(&RemoteConnectionState::Connected));
+
+        // A reconnect inside the grace period keeps the seat
+        loopback.send(RemotePeerEvent::Disconnected { player_uuid: remote_uuid });
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(3));
+        app.update();
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::ZERO);
+        assert!(app.world().resource::<Party>().player_map.contains_uuid(&remote_uuid));
+        loopback.send(RemotePeerEvent::Connected { player_uuid: remote_uuid });
+        app.update();
+        assert_eq!(app.world().get::<RemoteConnectionState>(remote_entity), Some(&RemoteConnectionState::Connected));
+
+        // Staying away past the grace period gives the seat up
+        loopback.send(RemotePeerEvent::Disconnected { player_uuid: remote_uuid });
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(6));
+        app.update();
+        app.world_mut().resource_mut::<Time>().advance_by(Duration::ZERO);
+        app.update();
+        assert!(!app.world().resource::<Party>().player_map.contains_uuid(&remote_uuid));
+        assert!(app.world().get_entity(remote_entity).is_none());
+        // The record stored on join goes with them
+        assert!(!app.world().resource::<PlayerStorage>().player_exists(&remote_uuid)?);
+
+        // Leaving drops the player right away
+        let leaving_uuid = Uuid::now_v7();
+        loopback.send(RemotePeerEvent::Connecting { player_uuid: leaving_uuid, username: None, player_type: PlayerType::PlayerAiRemote });
+        app.update();
+        assert!(app.world().resource::<Party>().player_map.contains_uuid(&leaving_uuid));
+        loopback.send(RemotePeerEvent::Left { player_uuid: leaving_uuid });
+        app.update();
+        app.update();
+        assert!(!app.world().resource::<Party>().player_map.contains_uuid(&leaving_uuid));
+        assert!(!app.world().resource::<PlayerStorage>().player_exists(&leaving_uuid)?);
+
+        // Local player types can not join through the transport
+        let local_uuid = Uuid::now_v7();
+        loopback.send(RemotePeerEvent::Connecting { player_uuid: local_uuid, username: None, player_type: PlayerType::PlayerLocal });
+        app.update();
+        assert!(!app.world().resource::<PlayerIndex>().contains(&local_uuid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_snapshot() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        for username in ["first_local", "second_local"] {
+            app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.add_local(username)
+            })?;
+            app.update();
+        }
+        let mut snapshot = app.world_mut().run_system_once(|party: Res<Party>, player_query: Query<PlayerData>| {
+            party.snapshot(&player_query)
+        })?;
+        let main_uuid = app.world().resource::<Party>().get_main_player_uuid()?.unwrap();
+        assert_eq!(snapshot.main_player_uuid, Some(main_uuid));
+        assert_eq!(snapshot.active_player, 3);
+        assert_eq!(snapshot.players.len(), 3);
+        assert_eq!(snapshot.players[1].seat, 2);
+        assert_eq!(snapshot.players[1].player_type, PlayerType::PlayerLocal);
+        assert_eq!(snapshot.players[1].username, Some("first_local".to_string()));
+
+        #[cfg(feature = "serde")]
+        {
+            let encoded = ron::to_string(&snapshot).unwrap();
+            let decoded: PartySnapshot = ron::from_str(&encoded).unwrap();
+            assert_eq!(decoded, snapshot);
+        }
+
+        // The second local leaves, a remote takes seat 3 and the active seat moves to 2
+        let dropped_uuid = snapshot.players[2].player_uuid;
+        let remote_uuid = Uuid::now_v7();
+        snapshot.players[2] = PartySnapshotPlayer {
+            seat: 3,
+            player_uuid: remote_uuid,
+            player_type: PlayerType::PlayerRemote,
+            username: Some("remote".to_string()),
+        };
+        snapshot.active_player = 2;
+        let applied = snapshot.clone();
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &applied)
+        })?;
+        app.update();
+        app.update();
+        let party = app.world().resource::<Party>();
+        assert_eq!(party.player_map.get(&3), Some(&remote_uuid));
+        assert!(!party.player_map.contains_uuid(&dropped_uuid));
+        assert_eq!(party.get_active_player_index()?, 2);
+        let player_index = app.world().resource::<PlayerIndex>();
+        assert!(player_index.get_entity(&dropped_uuid).is_none());
+        let remote_entity = player_index.get_entity(&remote_uuid).unwrap();
+        assert_eq!(app.world().get::<PlayerName>(remote_entity), Some(&PlayerName(Some("remote".to_string()))));
+        let rebuilt = app.world_mut().run_system_once(|party: Res<Party>, player_query: Query<PlayerData>| {
+            party.snapshot(&player_query)
+        })?;
+        assert_eq!(rebuilt, snapshot);
+
+        // Two players in one seat are refused
+        let mut doubled = snapshot.clone();
+        doubled.players[2].seat = 2;
+        let refused = app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &doubled).is_err()
+        });
+        assert!(refused);
+
+        // A seat past the party size and an active seat nobody holds are refused before the roster changes
+        let mut oversized = snapshot.clone();
+        oversized.players[2].seat = usize::MAX;
+        let mut unseated_active = snapshot.clone();
+        unseated_active.active_player = 4;
+        let refused = app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            let oversized = handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &oversized).is_err();
+            let unseated_active = handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &unseated_active).is_err();
+            oversized && unseated_active
+        });
+        assert!(refused);
+        assert_eq!(app.world().resource::<Party>().player_map.get(&3), Some(&remote_uuid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_snapshot_spawns_every_new_player() -> Result<(), ErrorTypePlayerHandler> {
+        let mut app = App::new();
+        app.insert_resource(DatabaseConnection::new(":memory:"))
+            .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                .main_player_email(PLAYER_EMAIL)
+                .main_player_username(PLAYER_USERNAME)
+                .party_size(4)
+                .build()
+            );
+        app.update();
+        let mut snapshot = app.world_mut().run_system_once(|party: Res<Party>, player_query: Query<PlayerData>| {
+            party.snapshot(&player_query)
+        })?;
+        app.world_mut().resource_mut::<Events<PlayerJoinedParty>>().clear();
+
+        // Every player the snapshot adds is spawned in the same frame
+        let joining_uuids = [Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7()];
+        for (n, player_uuid) in joining_uuids.iter().enumerate() {
+            snapshot.players.push(PartySnapshotPlayer {
+                seat: n + 2,
+                player_uuid: *player_uuid,
+                player_type: PlayerType::PlayerRemote,
+                username: Some(format!("remote_{}", n)),
+            });
+        }
+        app.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &snapshot)
+        })?;
+        app.update();
+
+        let joined: Vec<Uuid> = app.world_mut().resource_mut::<Events<PlayerJoinedParty>>().drain().map(|event| event.player_uuid).collect();
+        let records = app.world_mut().run_system_once(|db: Res<DatabaseConnection>, phi: Res<PlayerHandlerInterface>| {
+            phi.query_db_existing_players(&db)
+        })?;
+        for player_uuid in joining_uuids.iter() {
+            assert_eq!(joined.iter().filter(|joined_uuid| *joined_uuid == player_uuid).count(), 1);
+            assert_eq!(records.iter().filter(|record| record.get_uuid_string() == &player_uuid.to_string()).count(), 1);
+        }
+        assert_eq!(joined.len(), joining_uuids.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_party_deltas() -> Result<(), ErrorTypePlayerHandler> {
+        let build_app = || {
+            let mut app = App::new();
+            app.insert_resource(DatabaseConnection::new(":memory:"))
+                .add_plugins(BevyEasyPlayerHandlerPlugin::init()
+                    .main_player_email(PLAYER_EMAIL)
+                    .main_player_username(PLAYER_USERNAME)
+                    .party_size(4)
+                    .build()
+                );
+            app.update();
+            app
+        };
+        let mut host = build_app();
+        let mut client = build_app();
+        let snapshot = host.world_mut().run_system_once(|party: Res<Party>, player_query: Query<PlayerData>| {
+            party.snapshot(&player_query)
+        })?;
+        client.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.party.apply_snapshot(&mut handler.commands, &handler.player_index, &mut handler.plugin, &snapshot)
+        })?;
+        client.update();
+        let start_revision = client.world().resource::<Party>().get_revision();
+
+        // The host runs through a join, a swap, a rename and a removal
+        for username in ["first_local", "second_local"] {
+            host.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.add_local(username)
+            })?;
+            host.update();
+        }
+        host.world_mut().resource_mut::<Party>().player_map_swap_players(2, 3)?;
+        host.world_mut().run_system_once(|mut handler: PlayerHandler| {
+            handler.party.set_active_player_username(&mut handler.commands, &handler.player_index, &handler.player_query, "renamed")
+        })?;
+        let renamed_uuid = *host.world().resource::<Party>().player_map.get(&3).unwrap();
+        let removed_uuid = *host.world().resource::<Party>().player_map.get(&2).unwrap();
+        host.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            handler.remove(&removed_uuid)
+        })?;
+        host.update();
+
+        let deltas = host.world().resource::<Party>().deltas_since(start_revision);
+        assert_eq!(deltas.first().map(|delta| delta.revision), Some(start_revision + 1));
+        assert!(deltas.iter().any(|delta| matches!(&delta.change, PartyChange::Renamed { player_uuid, .. } if *player_uuid == renamed_uuid)));
+        for delta in deltas.iter() {
+            let delta = delta.clone();
+            client.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+                handler.party.apply_delta(&mut handler.commands, &handler.player_index, &handler.plugin, &delta)
+            })?;
+            client.update();
+        }
+        let host_party = host.world().resource::<Party>();
+        let client_party = client.world().resource::<Party>();
+        assert_eq!(client_party.player_map, host_party.player_map);
+        assert_eq!(client_party.get_active_player_index()?, host_party.get_active_player_index()?);
+        assert_eq!(client_party.get_revision(), host_party.get_revision());
+        let renamed_entity = client.world().resource::<PlayerIndex>().get_entity(&renamed_uuid).unwrap();
+        assert_eq!(client.world().get::<PlayerName>(renamed_entity), Some(&PlayerName(Some("renamed".to_string()))));
+        assert!(client.world().resource::<PlayerIndex>().get_entity(&removed_uuid).is_none());
+
+        // A revision already applied and one that skips ahead are both refused
+        let stale = deltas.last().unwrap().clone();
+        let mut skipped = stale.clone();
+        skipped.revision += 2;
+        let refused = client.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            let stale = handler.party.apply_delta(&mut handler.commands, &handler.player_index, &handler.plugin, &stale).is_err();
+            let skipped = handler.party.apply_delta(&mut handler.commands, &handler.player_index, &handler.plugin, &skipped).is_err();
+            stale && skipped
+        });
+        assert!(refused);
+
+        // A join far past the last seat and an active change to an empty seat are refused
+        let next_revision = client.world().resource::<Party>().get_revision() + 1;
+        let far_join = PartyDelta {
+            revision: next_revision,
+            change: PartyChange::Joined {
+                player_uuid: Uuid::now_v7(),
+                seat: usize::MAX,
+                player_type: PlayerType::PlayerRemote,
+                username: None,
+            },
+        };
+        let empty_active = PartyDelta {
+            revision: next_revision,
+            change: PartyChange::ActiveChanged { active_player: 4 },
+        };
+        let refused = client.world_mut().run_system_once(move |mut handler: PlayerHandler| {
+            let far_join = handler.party.apply_delta(&mut handler.commands, &handler.player_index, &handler.plugin, &far_join).is_err();
+            let empty_active = handler.party.apply_delta(&mut handler.commands, &handler.player_index, &handler.plugin, &empty_active).is_err();
+            far_join && empty_active
+        });
+        assert!(refused);
+        assert_eq!(client.world().resource::<Party>().get_revision(), next_revision - 1);
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -178,4 +178,36 @@ mod tests {
         player_set!(PlayerRemote::new, PLAYER_TYPE_REMOTE);
         Ok(())
     }
+    // --- PlayerBundle Tests --- //
+
+    #[test]
+    fn test_player_bundle_new_all_data() -> Result<(), ErrorTypePlayerHandler> {
+        player_new_all_data!(PlayerBundle::new, PLAYER_TYPE_LOCAL);
+        Ok(())
+    }
+
+    #[test]
+    fn test_player_bundle_new_uuid_missing() -> Result<(), ErrorTypePlayerHandler> {
+        player_new_uuid_missing!(PlayerBundle::new, PLAYER_TYPE_AI_LOCAL);
+        Ok(())
+    }
+
+    #[test]
+    fn test_player_bundle_set() -> Result<(), ErrorTypePlayerHandler> {
+        player_set!(PlayerBundle::new, PLAYER_TYPE_REMOTE);
+        Ok(())
+    }
+
+    #[test]
+    fn test_player_bundle_from_player() -> Result<(), ErrorTypePlayerHandler> {
+        let uuid = Uuid::now_v7();
+        let player = PlayerMain::new(None, Some(PLAYER_USERNAME.to_string()), Some(uuid), PLAYER_TYPE_MAIN);
+        let bundle = PlayerBundle::from_player(&player)?;
+
+        assert_eq!(bundle.id, PlayerId(uuid));
+        assert_eq!(bundle.kind, PlayerKind(PLAYER_TYPE_MAIN));
+        assert_eq!(bundle.name, PlayerName(Some(PLAYER_USERNAME.to_string())));
+        assert_eq!(bundle.email, PlayerEmail(None));
+        Ok(())
+    }
 }
\ No newline at end of file